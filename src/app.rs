@@ -1,4 +1,12 @@
+use crate::cli::CsvOptions;
+use crate::compute::parse_computed_column;
+use crate::diff::{DiffSummary, DIFF_CHANGED_COLUMN};
+use crate::export::write_dataframe;
+use crate::filter::build_filter_expr;
+use crate::loader::load_dataframe;
+use polars::lazy::frame::pivot::pivot_stable;
 use polars::prelude::*;
+use polars::sql::SQLContext;
 use ratatui::widgets::TableState;
 use std::collections::HashMap;
 
@@ -15,21 +23,11 @@ pub struct ColumnProfile {
 }
 
 const DEFAULT_COLUMN_WIDTH: u16 = 15;
-
-pub struct Config {
-    pub file_path: String,
-}
-
-impl Config {
-    pub fn new(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
-        args.next();
-        let file_path = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Please provide a valid CSV file path"),
-        };
-        Ok(Config { file_path })
-    }
-}
+const MAX_HISTORY: usize = 100;
+/// Rows fetched per keystroke while a filter is typed over a lazy scan.
+const FILTER_PREVIEW_ROWS: usize = 200;
+/// Blank cells between table columns (ratatui's default).
+const COLUMN_SPACING: usize = 1;
 
 #[derive(Debug)]
 pub enum Mode {
@@ -40,6 +38,45 @@ pub enum Mode {
     Plot,
     ColumnsView,
     UniqueValues,
+    Export,
+    ConfirmOverwrite,
+    Sql,
+    FilterList,
+    PivotSetup,
+    Join,
+    JoinSetup,
+    Compute,
+    ColumnChooser,
+}
+
+/// Table name the loaded data is registered under in SQL queries.
+pub const SQL_TABLE_NAME: &str = "self";
+
+/// One-shot message shown in the status bar until the next key press.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusMessage {
+    Info(String),
+    Error(String),
+}
+
+/// A filter typed on one column with `f`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub column: usize,
+    pub query: String,
+    pub enabled: bool,
+    pub inverted: bool, // keep the rows the query rejects
+}
+
+impl Filter {
+    pub fn new(column: usize, query: impl Into<String>) -> Filter {
+        Filter {
+            column,
+            query: query.into(),
+            enabled: true,
+            inverted: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -49,19 +86,282 @@ pub enum PlotType {
     Histogram,
 }
 
-#[derive(Debug)]
+/// One column of a (possibly multi-column) sort, in priority order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub column: usize,
+    pub direction: SortDirection,
+    pub nulls_last: bool,
+}
+
+impl SortKey {
+    pub fn new(column: usize, direction: SortDirection) -> SortKey {
+        SortKey {
+            column,
+            direction,
+            nulls_last: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum SortDirection {
     Ascending,
     Descending,
 }
 
+impl SortDirection {
+    fn toggle(&mut self) {
+        *self = match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        };
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AggFunc {
     Sum,
     Mean,
+    Median,
     Count,
+    NUnique,
+    NullCount,
     Min,
     Max,
+    Std,
+    Var,
+    First,
+    Last,
+    Quantile(u8), // percentile, e.g. 90 for p90
+    Concat,
+}
+
+/// The order `a` cycles through.
+const AGG_CYCLE: [AggFunc; 16] = [
+    AggFunc::Sum,
+    AggFunc::Mean,
+    AggFunc::Median,
+    AggFunc::Count,
+    AggFunc::NUnique,
+    AggFunc::NullCount,
+    AggFunc::Min,
+    AggFunc::Max,
+    AggFunc::Std,
+    AggFunc::Var,
+    AggFunc::First,
+    AggFunc::Last,
+    AggFunc::Quantile(50),
+    AggFunc::Quantile(90),
+    AggFunc::Quantile(99),
+    AggFunc::Concat,
+];
+
+impl AggFunc {
+    /// Parse a name as used in `--agg` and result column names: `sum`, `p90`, ...
+    pub fn parse(s: &str) -> Option<AggFunc> {
+        let s = s.trim().to_lowercase();
+        if let Some(p) = s.strip_prefix('p') {
+            return p.parse().ok().filter(|p| *p <= 100).map(AggFunc::Quantile);
+        }
+        Some(match s.as_str() {
+            "sum" => AggFunc::Sum,
+            "mean" | "avg" => AggFunc::Mean,
+            "median" => AggFunc::Median,
+            "count" => AggFunc::Count,
+            "n_unique" | "nunique" => AggFunc::NUnique,
+            "null_count" | "nulls" => AggFunc::NullCount,
+            "min" => AggFunc::Min,
+            "max" => AggFunc::Max,
+            "std" => AggFunc::Std,
+            "var" => AggFunc::Var,
+            "first" => AggFunc::First,
+            "last" => AggFunc::Last,
+            "concat" => AggFunc::Concat,
+            _ => return None,
+        })
+    }
+
+    /// Suffix of the result column, e.g. `salary_p90`.
+    pub fn name(&self) -> String {
+        match self {
+            AggFunc::Sum => "sum".to_string(),
+            AggFunc::Mean => "mean".to_string(),
+            AggFunc::Median => "median".to_string(),
+            AggFunc::Count => "count".to_string(),
+            AggFunc::NUnique => "n_unique".to_string(),
+            AggFunc::NullCount => "null_count".to_string(),
+            AggFunc::Min => "min".to_string(),
+            AggFunc::Max => "max".to_string(),
+            AggFunc::Std => "std".to_string(),
+            AggFunc::Var => "var".to_string(),
+            AggFunc::First => "first".to_string(),
+            AggFunc::Last => "last".to_string(),
+            AggFunc::Quantile(p) => format!("p{}", p),
+            AggFunc::Concat => "concat".to_string(),
+        }
+    }
+
+    /// Marker shown in headers and the status bar.
+    pub fn symbol(&self) -> String {
+        match self {
+            AggFunc::Sum => "Σ".to_string(),
+            AggFunc::Mean => "μ".to_string(),
+            AggFunc::Median => "x̃".to_string(),
+            AggFunc::Count => "#".to_string(),
+            AggFunc::NUnique => "#u".to_string(),
+            AggFunc::NullCount => "∅".to_string(),
+            AggFunc::Min => "↓".to_string(),
+            AggFunc::Max => "↑".to_string(),
+            AggFunc::Std => "σ".to_string(),
+            AggFunc::Var => "σ²".to_string(),
+            AggFunc::First => "1st".to_string(),
+            AggFunc::Last => "last".to_string(),
+            AggFunc::Quantile(p) => format!("p{}", p),
+            AggFunc::Concat => "⧺".to_string(),
+        }
+    }
+
+    fn expr(&self, name: &str) -> Expr {
+        self.apply(col(name))
+            .alias(format!("{}_{}", name, self.name()))
+    }
+
+    fn apply(&self, c: Expr) -> Expr {
+        match self {
+            AggFunc::Sum => c.sum(),
+            AggFunc::Mean => c.mean(),
+            AggFunc::Median => c.median(),
+            AggFunc::Count => c.count(),
+            AggFunc::NUnique => c.n_unique(),
+            AggFunc::NullCount => c.null_count(),
+            AggFunc::Min => c.min(),
+            AggFunc::Max => c.max(),
+            AggFunc::Std => c.std(1),
+            AggFunc::Var => c.var(1),
+            AggFunc::First => c.first(),
+            AggFunc::Last => c.last(),
+            AggFunc::Quantile(p) => c.quantile(lit(*p as f64 / 100.0), QuantileMethod::Linear),
+            AggFunc::Concat => c.cast(DataType::String).str().join(", ", true),
+        }
+    }
+
+    /// The next (or previous) function in the `a` cycle, wrapping around.
+    pub fn cycled(&self, forward: bool) -> AggFunc {
+        let n = AGG_CYCLE.len();
+        let i = AGG_CYCLE.iter().position(|f| f == self).unwrap_or(0);
+        let next = if forward {
+            (i + 1) % n
+        } else {
+            (i + n - 1) % n
+        };
+        AGG_CYCLE[next].clone()
+    }
+}
+
+/// Label of the totals row and column of a pivot table.
+pub const PIVOT_TOTAL: &str = "Total";
+
+/// A pivot table: one row per `rows` value, one column per `columns` value and
+/// `func` of `values` in each cell. Indices refer to the unpivoted headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PivotSpec {
+    pub rows: usize,
+    pub columns: usize,
+    pub values: usize,
+    pub func: AggFunc,
+    pub totals: bool,
+}
+
+/// How rows of the current view are matched against the joined file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+    Anti, // rows without a match; the file's columns are not added
+    Semi, // rows with a match; the file's columns are not added
+}
+
+impl JoinKind {
+    pub fn label(&self) -> &str {
+        match self {
+            JoinKind::Inner => "inner",
+            JoinKind::Left => "left",
+            JoinKind::Anti => "anti",
+            JoinKind::Semi => "semi",
+        }
+    }
+
+    pub fn cycled(&self, forward: bool) -> JoinKind {
+        const KINDS: [JoinKind; 4] = [
+            JoinKind::Inner,
+            JoinKind::Left,
+            JoinKind::Anti,
+            JoinKind::Semi,
+        ];
+        let i = KINDS.iter().position(|k| k == self).unwrap_or(0);
+        let next = if forward { i + 1 } else { i + KINDS.len() - 1 };
+        KINDS[next % KINDS.len()]
+    }
+
+    fn join_type(&self) -> JoinType {
+        match self {
+            JoinKind::Inner => JoinType::Inner,
+            JoinKind::Left => JoinType::Left,
+            JoinKind::Anti => JoinType::Anti,
+            JoinKind::Semi => JoinType::Semi,
+        }
+    }
+}
+
+/// A join of the current view with another file. Rows match when they agree on
+/// every key pair, each a view header index and a file column index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinSpec {
+    pub path: String,
+    pub keys: Vec<(usize, usize)>,
+    pub kind: JoinKind,
+}
+
+impl JoinSpec {
+    /// Labels of the join setup popup's fields, in display order: both sides
+    /// of each key pair, then the join type.
+    pub fn fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        for i in 1..=self.keys.len() {
+            let n = if self.keys.len() > 1 {
+                format!(" {}", i)
+            } else {
+                String::new()
+            };
+            fields.push(format!("View key{}", n));
+            fields.push(format!("File key{}", n));
+        }
+        fields.push("Type".to_string());
+        fields
+    }
+}
+
+/// The frame a computed column was added to. When the SQL result or joined
+/// data is dropped, its computed columns are added again to the frame beneath.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataLayer {
+    Loaded,
+    Joined,
+    Sql,
+}
+
+/// Fields of the pivot setup popup, in display order.
+pub const PIVOT_FIELDS: [&str; 5] = ["Rows", "Columns", "Values", "Aggregate", "Totals"];
+
+/// How the table arranges its columns, set in the column chooser. Columns are
+/// named rather than indexed so the layout survives group-bys, SQL and joins;
+/// names the view doesn't have are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnLayout {
+    pub order: Vec<String>, // display order; unlisted columns follow in data order
+    pub hidden: Vec<String>, // not rendered, but still filterable
+    pub pinned: Vec<String>, // shown ahead of the others
 }
 
 #[derive(Default)]
@@ -74,25 +374,31 @@ pub struct ColumnStats {
 }
 
 pub struct App {
-    pub df: DataFrame,        // original data
-    pub view: DataFrame,      // current filtered/sorted result
-    pub headers: Vec<String>, // column names for display
+    pub df: DataFrame,                // original data (schema only in lazy mode)
+    pub view: DataFrame,              // current filtered/sorted result
+    pub headers: Vec<String>,         // column names for display
+    pub source: Option<LazyFrame>,    // file scan, set when opened in lazy mode
+    pub lazy_view: Option<LazyFrame>, // source with filters and sort pushed down
+    pub lazy_height: usize,
+    lazy_window: Option<(usize, usize, DataFrame)>, // cached (offset, len, rows) of the viewport
     pub state: TableState,
     pub should_quit: bool,
     pub file_path: String,
+    pub file_count: usize,
+    pub csv_options: CsvOptions, // reader options from the command line, for files opened later
     pub column_widths: Vec<u16>,
     pub mode: Mode,
     pub search_query: String,
     pub search_results: Vec<usize>,
     pub search_cursor: usize,
-    pub filters: Vec<(usize, String)>,
+    pub filters: Vec<Filter>,
     pub filter_input: String,
-    pub sort_column: Option<usize>,
-    pub sort_direction: SortDirection,
+    pub filter_error: Option<String>, // why the typed filter doesn't parse or evaluate
+    pub sort_keys: Vec<SortKey>,      // empty when unsorted; first is the primary key
     pub show_stats: bool,
     pub show_help: bool,
     pub groupby_keys: Vec<usize>,
-    pub groupby_aggs: HashMap<usize, AggFunc>,
+    pub groupby_aggs: HashMap<usize, Vec<AggFunc>>,
     pub groupby_active: bool,
     pub saved_headers: Vec<String>,
     pub saved_column_widths: Vec<u16>,
@@ -102,62 +408,113 @@ pub struct App {
     pub columns_profile: Vec<ColumnProfile>,
     pub columns_view_state: TableState,
     pub view_offset: usize,
+    pub column_offset: usize, // first scrolling column shown, past the frozen ones
+    pub frozen_columns: usize, // leading shown columns kept in place while scrolling
     pub unique_values: Vec<(String, usize)>,
     pub unique_values_filtered: Vec<(String, usize)>,
     pub unique_values_query: String,
     pub unique_values_state: TableState,
     pub unique_values_col: usize,
+    pub export_input: String,
+    pub status_message: Option<StatusMessage>,
+    pub filter_list_state: TableState,
+    pub filter_impacts: Vec<usize>, // rows each filter removes (or would, when disabled)
+    pub filter_edit: Option<String>, // query being edited in the filter list
+    pub sql_input: String,
+    pub sql_view: Option<DataFrame>, // result of the last SQL query, filtered in place of `df`
+    pub sql_query: String,           // the query that produced `sql_view`
+    sql_saved_column_widths: Vec<u16>,
+    pub join_view: Option<DataFrame>, // result of the joins, filtered in place of `df`
+    pub joins: Vec<String>,           // description of each join applied, in order
+    join_saved_column_widths: Vec<u16>,
+    pub join_input: String,
+    pub join_setup: JoinSpec,
+    pub join_right: Option<DataFrame>, // the file being joined, while picking keys
+    pub join_form_state: TableState,
+    pub drill_stack: Vec<(String, ViewSnapshot)>, // grouped views drilled into, with their group label
+    pub compute_input: String,
+    pub compute_error: Option<String>, // why the typed column expression failed
+    pub computed_columns: Vec<(String, DataLayer)>, // definitions of the columns added with `c`, in order
+    pub diff: Option<DiffSummary>, // set when the view shows `iron-sight diff` output
+    pub show_diff_summary: bool,
+    pub pivot: Option<PivotSpec>, // set while the view shows a pivot table
+    pub pivot_setup: PivotSpec,   // being edited in the pivot popup
+    pub pivot_form_state: TableState,
+    pub column_layout: ColumnLayout,
+    pub column_chooser_state: TableState,
+    undo_stack: Vec<ViewSnapshot>,
+    redo_stack: Vec<ViewSnapshot>,
 }
 
-/// Build a polars filter expression for a column and query string.
-/// Supports comparison operators (>, <, >=, <=, =, !=) for numeric values.
-/// Falls back to case-insensitive substring matching for everything else.
-fn build_filter_expr(col_name: &str, query: &str) -> Expr {
-    let q = query.trim();
-    let (op, rest) = if let Some(r) = q.strip_prefix(">=") {
-        (">=", r.trim())
-    } else if let Some(r) = q.strip_prefix("<=") {
-        ("<=", r.trim())
-    } else if let Some(r) = q.strip_prefix("!=") {
-        ("!=", r.trim())
-    } else if let Some(r) = q.strip_prefix('>') {
-        (">", r.trim())
-    } else if let Some(r) = q.strip_prefix('<') {
-        ("<", r.trim())
-    } else if let Some(r) = q.strip_prefix('=') {
-        ("=", r.trim())
-    } else {
-        ("", q)
-    };
-
-    if !op.is_empty() {
-        if let Ok(value) = rest.parse::<f64>() {
-            return match op {
-                ">=" => col(col_name).gt_eq(lit(value)),
-                "<=" => col(col_name).lt_eq(lit(value)),
-                "!=" => col(col_name).neq(lit(value)),
-                ">" => col(col_name).gt(lit(value)),
-                "<" => col(col_name).lt(lit(value)),
-                _ => col(col_name).eq(lit(value)),
-            };
-        }
-        // Non-numeric value with = / != : exact string match.
-        if op == "=" {
-            return col(col_name)
-                .cast(DataType::String)
-                .eq(lit(rest.to_string()));
-        }
-        if op == "!=" {
-            return col(col_name)
-                .cast(DataType::String)
-                .neq(lit(rest.to_string()));
-        }
+/// Everything a view-changing operation touches, so it can be undone.
+/// Frames are cheap to clone (their columns are reference-counted).
+#[derive(Clone)]
+pub struct ViewSnapshot {
+    df: DataFrame,
+    source: Option<LazyFrame>,
+    computed_columns: Vec<(String, DataLayer)>,
+    view: DataFrame,
+    lazy_view: Option<LazyFrame>,
+    lazy_height: usize,
+    headers: Vec<String>,
+    column_widths: Vec<u16>,
+    filters: Vec<Filter>,
+    sort_keys: Vec<SortKey>,
+    groupby_keys: Vec<usize>,
+    groupby_aggs: HashMap<usize, Vec<AggFunc>>,
+    groupby_active: bool,
+    saved_headers: Vec<String>,
+    saved_column_widths: Vec<u16>,
+    sql_view: Option<DataFrame>,
+    sql_query: String,
+    sql_saved_column_widths: Vec<u16>,
+    join_view: Option<DataFrame>,
+    joins: Vec<String>,
+    join_saved_column_widths: Vec<u16>,
+    drill_stack: Vec<(String, ViewSnapshot)>,
+    pivot: Option<PivotSpec>,
+    column_layout: ColumnLayout,
+    frozen_columns: usize,
+    selected: Option<usize>,
+    selected_column: Option<usize>,
+}
+
+impl ViewSnapshot {
+    /// Whether two snapshots show the same view. The frames follow from the
+    /// other fields, and the cursor alone doesn't count as a change.
+    fn same_view(&self, other: &ViewSnapshot) -> bool {
+        self.headers == other.headers
+            && self.column_widths == other.column_widths
+            && self.filters == other.filters
+            && self.sort_keys == other.sort_keys
+            && self.groupby_keys == other.groupby_keys
+            && self.groupby_aggs == other.groupby_aggs
+            && self.groupby_active == other.groupby_active
+            && self.sql_view.is_some() == other.sql_view.is_some()
+            && self.sql_query == other.sql_query
+            && self.joins == other.joins
+            && self.computed_columns == other.computed_columns
+            && self.drill_stack.len() == other.drill_stack.len()
+            && self.pivot == other.pivot
+            && self.column_layout == other.column_layout
+            && self.frozen_columns == other.frozen_columns
     }
+}
 
-    col(col_name)
-        .cast(DataType::String)
-        .str()
-        .contains(lit(query), false)
+/// Cast a series to strings for display. Nested values (lists, arrays) can't be
+/// cast by polars, so they are formatted element by element as `[a, b, c]`.
+pub fn to_display_strings(s: &Series) -> Option<Series> {
+    if !s.dtype().is_nested() {
+        return s.cast(&DataType::String).ok();
+    }
+    let values: Vec<Option<String>> = s
+        .iter()
+        .map(|v| match v {
+            AnyValue::Null => None,
+            v => Some(v.to_string()),
+        })
+        .collect();
+    Some(Series::new(s.name().clone(), values))
 }
 
 impl App {
@@ -173,9 +530,15 @@ impl App {
             df,
             view,
             headers,
+            source: None,
+            lazy_view: None,
+            lazy_height: 0,
+            lazy_window: None,
             state: TableState::default(),
             should_quit: false,
             file_path,
+            file_count: 1,
+            csv_options: CsvOptions::default(),
             column_widths: vec![DEFAULT_COLUMN_WIDTH; column_count],
             mode: Mode::Normal,
             search_query: String::new(),
@@ -183,8 +546,8 @@ impl App {
             search_cursor: 0,
             filter_input: String::new(),
             filters: Vec::new(),
-            sort_column: None,
-            sort_direction: SortDirection::Ascending,
+            filter_error: None,
+            sort_keys: Vec::new(),
             show_stats: false,
             show_help: false,
             groupby_keys: Vec::new(),
@@ -198,11 +561,52 @@ impl App {
             columns_profile: Vec::new(),
             columns_view_state: TableState::default(),
             view_offset: 0,
+            column_offset: 0,
+            frozen_columns: 0,
             unique_values: Vec::new(),
             unique_values_filtered: Vec::new(),
             unique_values_query: String::new(),
             unique_values_state: TableState::default(),
             unique_values_col: 0,
+            export_input: String::new(),
+            status_message: None,
+            filter_list_state: TableState::default(),
+            filter_impacts: Vec::new(),
+            filter_edit: None,
+            sql_input: String::new(),
+            sql_view: None,
+            sql_query: String::new(),
+            sql_saved_column_widths: Vec::new(),
+            join_view: None,
+            joins: Vec::new(),
+            join_saved_column_widths: Vec::new(),
+            join_input: String::new(),
+            join_setup: JoinSpec {
+                path: String::new(),
+                keys: vec![(0, 0)],
+                kind: JoinKind::Left,
+            },
+            join_right: None,
+            join_form_state: TableState::default(),
+            drill_stack: Vec::new(),
+            compute_input: String::new(),
+            compute_error: None,
+            computed_columns: Vec::new(),
+            diff: None,
+            show_diff_summary: false,
+            pivot: None,
+            pivot_setup: PivotSpec {
+                rows: 0,
+                columns: 1,
+                values: 2,
+                func: AggFunc::Sum,
+                totals: false,
+            },
+            pivot_form_state: TableState::default(),
+            column_layout: ColumnLayout::default(),
+            column_chooser_state: TableState::default(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
         };
         if !app.df.is_empty() {
             app.state.select(Some(0));
@@ -211,20 +615,109 @@ impl App {
         app
     }
 
+    /// Open the app over a lazy scan. Only the schema is read up front; rows are
+    /// materialized on demand for the viewport.
+    pub fn new_lazy(mut source: LazyFrame, file_path: String) -> PolarsResult<App> {
+        let schema = source.collect_schema()?;
+        let mut app = App::new(DataFrame::empty_with_schema(&schema), file_path);
+        app.source = Some(source);
+        app.update_filter();
+        if app.lazy_height > 0 {
+            app.state.select(Some(0));
+            app.state.select_column(Some(0));
+        }
+        Ok(app)
+    }
+
+    /// The file name(s) shown in the table title and status bar.
+    pub fn source_label(&self) -> String {
+        if self.file_count > 1 {
+            format!("{} ({} files)", self.file_path, self.file_count)
+        } else {
+            self.file_path.clone()
+        }
+    }
+
+    /// Whether there is anything to inspect. In lazy mode the row count of the
+    /// source is unknown, so any scan with columns counts.
+    pub fn has_data(&self) -> bool {
+        match self.source {
+            Some(_) => self.df.width() > 0,
+            None => !self.df.is_empty(),
+        }
+    }
+
+    /// Number of rows in the current view.
+    pub fn row_count(&self) -> usize {
+        if self.lazy_view.is_some() {
+            self.lazy_height
+        } else {
+            self.view.height()
+        }
+    }
+
+    /// Rows `offset..offset + len` of the current view. In lazy mode only this
+    /// window is collected, and it is cached until the view changes. A cached
+    /// window that covers the rows is sliced instead.
+    pub fn visible_rows(&mut self, offset: usize, len: usize) -> DataFrame {
+        let Some(lf) = &self.lazy_view else {
+            return self.view.slice(offset as i64, len);
+        };
+        if let Some((o, l, df)) = &self.lazy_window {
+            if *o <= offset && offset + len <= o + l {
+                return df.slice((offset - o) as i64, len);
+            }
+        }
+        let df = lf
+            .clone()
+            .slice(offset as i64, len as IdxSize)
+            .collect()
+            .unwrap_or_else(|_| self.df.clone());
+        self.lazy_window = Some((offset, len, df.clone()));
+        df
+    }
+
+    /// A single column of the current view, collecting only that column in lazy mode.
+    pub fn view_column(&self, name: &str) -> Option<Column> {
+        match &self.lazy_view {
+            Some(lf) => lf
+                .clone()
+                .select([col(name)])
+                .collect()
+                .ok()?
+                .column(name)
+                .ok()
+                .cloned(),
+            None => self.view.column(name).ok().cloned(),
+        }
+    }
+
+    fn view_lazy(&self) -> LazyFrame {
+        match &self.lazy_view {
+            Some(lf) => lf.clone(),
+            None => self.view.clone().lazy(),
+        }
+    }
+
+    /// The whole current view, collecting every row in lazy mode.
+    pub fn collect_view(&self) -> PolarsResult<DataFrame> {
+        self.view_lazy().collect()
+    }
+
     pub fn update_search(&mut self) {
         let current_column = self.state.selected_column().unwrap_or(0);
-        if self.headers.is_empty() || current_column >= self.headers.len() || self.view.is_empty() {
+        if self.headers.is_empty() || current_column >= self.headers.len() || self.row_count() == 0
+        {
             self.search_results.clear();
             return;
         }
         let col_name = &self.headers[current_column];
         let query = self.search_query.to_lowercase();
         let Some(series) = self
-            .view
-            .column(col_name)
-            .ok()
+            .view_column(col_name)
+            .as_ref()
             .and_then(|c| c.as_series())
-            .and_then(|s| s.cast(&DataType::String).ok())
+            .and_then(to_display_strings)
         else {
             self.search_results.clear();
             return;
@@ -242,58 +735,247 @@ impl App {
         self.search_cursor = 0;
     }
 
-    pub fn update_filter(&mut self) {
-        let mut mask = lit(true);
-        for (colidx, query) in &self.filters {
-            let col_name = &self.headers[*colidx];
-            mask = mask.and(build_filter_expr(col_name, query));
+    /// The rows the filtering starts from: the SQL result when there is one.
+    /// The frame filters apply to when it isn't the loaded data: the SQL result,
+    /// or else the joined data.
+    fn derived_base(&self) -> Option<&DataFrame> {
+        self.sql_view.as_ref().or(self.join_view.as_ref())
+    }
+
+    fn filter_base(&self) -> LazyFrame {
+        match (self.derived_base(), &self.source) {
+            (Some(df), _) => df.clone().lazy(),
+            (None, Some(source)) => source.clone(),
+            (None, None) => self.df.clone().lazy(),
         }
-        if !self.filter_input.is_empty() {
-            let col_name = &self.headers[self.state.selected_column().unwrap_or(0)];
-            mask = mask.and(build_filter_expr(col_name, &self.filter_input));
+    }
+
+    /// AND together the enabled filters and the one being typed. `toggled` flips
+    /// whether that filter takes part, for measuring its impact.
+    fn filter_mask(&self, toggled: Option<usize>) -> (Expr, Option<String>) {
+        let schema = self.derived_base().unwrap_or(&self.df).schema().clone();
+        // Starting from `lit(true)` would keep a scan from pruning hive
+        // partitions, so the first predicate starts the mask.
+        let mut mask: Option<Expr> = None;
+        let mut error = None;
+        let typed = (!self.filter_input.is_empty()).then(|| {
+            Filter::new(
+                self.state.selected_column().unwrap_or(0),
+                self.filter_input.clone(),
+            )
+        });
+        for (i, filter) in self.filters.iter().chain(typed.as_ref()).enumerate() {
+            if filter.enabled == (toggled == Some(i)) {
+                continue;
+            }
+            match build_filter_expr(&self.headers[filter.column], &filter.query, &schema) {
+                Ok(expr) => {
+                    let expr = if filter.inverted { expr.not() } else { expr };
+                    mask = Some(match mask {
+                        Some(mask) => mask.and(expr),
+                        None => expr,
+                    });
+                }
+                Err(err) => error = Some(err),
+            }
         }
-        let filtered = self
-            .df
-            .clone()
-            .lazy()
-            .filter(mask)
-            .collect()
-            .unwrap_or(self.df.clone());
+        (mask.unwrap_or(lit(true)), error)
+    }
+
+    fn count_rows(&self, mask: Expr) -> PolarsResult<usize> {
+        let df = self.filter_base().filter(mask).select([len()]).collect()?;
+        Ok(df.get_columns()[0].get(0)?.extract::<usize>().unwrap_or(0))
+    }
+
+    /// How many rows each filter removes given the others, or would remove if
+    /// it were enabled.
+    pub fn build_filter_impacts(&mut self) {
+        let (all, _) = self.filter_mask(None);
+        let current = self.count_rows(all).unwrap_or(0);
+        self.filter_impacts = (0..self.filters.len())
+            .map(|i| {
+                let (mask, _) = self.filter_mask(Some(i));
+                let toggled = self.count_rows(mask).unwrap_or(0);
+                current.abs_diff(toggled)
+            })
+            .collect();
+    }
+
+    pub fn update_filter(&mut self) {
+        self.refilter(true);
+    }
+
+    /// Re-filter as the filter prompt is typed in. Over a lazy scan only the
+    /// first rows are fetched; the rows are counted once the filter is entered.
+    pub fn preview_filter(&mut self) {
+        self.refilter(false);
+    }
 
+    fn refilter(&mut self, count: bool) {
+        let (mask, error) = self.filter_mask(None);
+        self.filter_error = error;
+        let base = self.derived_base().unwrap_or(&self.df).clone();
         self.view_offset = 0;
-        self.view = if let Some(sort_col) = self.sort_column {
-            let col_name = &self.headers[sort_col];
-            let opts = SortMultipleOptions::default()
-                .with_order_descending(matches!(self.sort_direction, SortDirection::Descending));
-            match filtered.sort([col_name], opts) {
+        if let (Some(source), false) = (&self.source, self.derived_base().is_some()) {
+            let mut lf = source.clone().filter(mask);
+            if !self.sort_keys.is_empty() {
+                let (by, opts) = self.sort_options();
+                lf = lf.sort(by, opts);
+            }
+            self.lazy_window = None;
+            if !count {
+                let head = lf
+                    .clone()
+                    .slice(0, FILTER_PREVIEW_ROWS as IdxSize)
+                    .collect();
+                self.lazy_height = match head {
+                    Ok(df) => {
+                        self.lazy_window = Some((0, df.height(), df.clone()));
+                        df.height()
+                    }
+                    Err(err) => {
+                        self.filter_error = Some(err.to_string());
+                        0
+                    }
+                };
+                self.lazy_view = Some(lf);
+                return;
+            }
+            self.lazy_height = match lf.clone().select([len()]).collect() {
+                Ok(df) => df.get_columns()[0]
+                    .get(0)
+                    .ok()
+                    .and_then(|v| v.extract::<usize>())
+                    .unwrap_or(0),
+                Err(err) => {
+                    self.filter_error = Some(err.to_string());
+                    0
+                }
+            };
+            self.lazy_view = Some(lf);
+            if !self.search_query.is_empty() {
+                self.update_search();
+            }
+            return;
+        }
+
+        let filtered = match base.clone().lazy().filter(mask).collect() {
+            Ok(df) => df,
+            Err(err) => {
+                self.filter_error = Some(err.to_string());
+                base.clone()
+            }
+        };
+
+        self.view = if self.sort_keys.is_empty() {
+            filtered
+        } else {
+            let (by, opts) = self.sort_options();
+            match filtered.sort(by, opts) {
                 Ok(sorted) => sorted,
                 Err(_) => filtered,
             }
-        } else {
-            filtered
         };
         if !self.search_query.is_empty() {
             self.update_search();
         }
     }
 
+    /// Column names and options for sorting by `sort_keys`. The sort is stable,
+    /// so rows that tie on every key keep their order.
+    fn sort_options(&self) -> (Vec<String>, SortMultipleOptions) {
+        let by = self
+            .sort_keys
+            .iter()
+            .map(|k| self.headers[k.column].clone())
+            .collect();
+        let opts = SortMultipleOptions::default()
+            .with_order_descending_multi(
+                self.sort_keys
+                    .iter()
+                    .map(|k| matches!(k.direction, SortDirection::Descending)),
+            )
+            .with_nulls_last_multi(self.sort_keys.iter().map(|k| k.nulls_last))
+            .with_maintain_order(true);
+        (by, opts)
+    }
+
+    fn sort_key_position(&self, col_idx: usize) -> Option<usize> {
+        self.sort_keys.iter().position(|k| k.column == col_idx)
+    }
+
+    /// Sort by the selected column alone, or flip its direction if it is already
+    /// a sort key.
     pub fn sort_by_column(&mut self) {
         let current_column = self.state.selected_column().unwrap_or(0);
-        if self.sort_column == Some(current_column) {
-            self.sort_direction = match self.sort_direction {
-                SortDirection::Ascending => SortDirection::Descending,
-                SortDirection::Descending => SortDirection::Ascending,
-            };
-        } else {
-            self.sort_column = Some(current_column);
-            self.sort_direction = SortDirection::Ascending;
+        match self.sort_key_position(current_column) {
+            Some(pos) => self.sort_keys[pos].direction.toggle(),
+            None => {
+                self.sort_keys = vec![SortKey::new(current_column, SortDirection::Ascending)];
+            }
         }
-        let col_name = &self.headers[current_column];
-        let opts = SortMultipleOptions::default()
-            .with_order_descending(matches!(self.sort_direction, SortDirection::Descending));
-        self.view = match self.view.sort([col_name], opts) {
-            Ok(sorted) => sorted,
-            Err(_) => self.view.clone(),
+        self.apply_sort();
+    }
+
+    /// Add the selected column as the lowest-priority sort key, or flip its
+    /// direction if it is already one.
+    pub fn add_sort_column(&mut self) {
+        let current_column = self.state.selected_column().unwrap_or(0);
+        match self.sort_key_position(current_column) {
+            Some(pos) => self.sort_keys[pos].direction.toggle(),
+            None => self
+                .sort_keys
+                .push(SortKey::new(current_column, SortDirection::Ascending)),
+        }
+        self.apply_sort();
+    }
+
+    pub fn remove_sort_column(&mut self) {
+        let current_column = self.state.selected_column().unwrap_or(0);
+        if let Some(pos) = self.sort_key_position(current_column) {
+            self.sort_keys.remove(pos);
+            self.apply_sort();
+        }
+    }
+
+    /// Switch the selected sort key between nulls first and nulls last.
+    pub fn toggle_sort_nulls(&mut self) {
+        let current_column = self.state.selected_column().unwrap_or(0);
+        if let Some(pos) = self.sort_key_position(current_column) {
+            self.sort_keys[pos].nulls_last = !self.sort_keys[pos].nulls_last;
+            self.apply_sort();
+        }
+    }
+
+    /// Re-sort the current view (or group-by result) by `sort_keys`.
+    pub fn apply_sort(&mut self) {
+        if self.lazy_view.is_some() {
+            self.update_filter();
+            return;
+        }
+        if self.sort_keys.is_empty() {
+            // Without a group-by or pivot the unsorted order can be rebuilt from the source.
+            if !self.groupby_active && self.pivot.is_none() {
+                self.update_filter();
+            }
+            return;
+        }
+        let (by, opts) = self.sort_options();
+        // A pivot's totals row stays at the bottom, whatever the order above it.
+        let totals = self.pivot.as_ref().is_some_and(|p| p.totals) && self.view.height() > 0;
+        let (rows, footer) = if totals {
+            let body = self.view.height() - 1;
+            (
+                self.view.slice(0, body),
+                Some(self.view.slice(body as i64, 1)),
+            )
+        } else {
+            (self.view.clone(), None)
+        };
+        self.view = match (rows.sort(by, opts), footer) {
+            (Ok(sorted), Some(footer)) => sorted.vstack(&footer).unwrap_or(self.view.clone()),
+            (Ok(sorted), None) => sorted,
+            (Err(_), _) => self.view.clone(),
         };
         if !self.search_query.is_empty() {
             self.update_search();
@@ -306,11 +988,9 @@ impl App {
             let header_width = label.chars().count() as u16;
             let col_name = self.headers[col_idx].clone();
             let max_data = self
-                .view
-                .column(&col_name)
-                .ok()
+                .view_column(&col_name)
                 .and_then(|col| {
-                    let cast = col.as_series()?.cast(&DataType::String).ok()?;
+                    let cast = to_display_strings(col.as_series()?)?;
                     let max = cast
                         .str()
                         .ok()?
@@ -333,11 +1013,9 @@ impl App {
             let header_width = label.chars().count() as u16;
             let col_name = self.headers[col_idx].clone();
             let max_data = self
-                .view
-                .column(&col_name)
-                .ok()
+                .view_column(&col_name)
                 .and_then(|col| {
-                    let cast = col.as_series()?.cast(&DataType::String).ok()?;
+                    let cast = to_display_strings(col.as_series()?)?;
                     cast.str()
                         .ok()?
                         .into_iter()
@@ -353,98 +1031,372 @@ impl App {
 
     pub fn header_label(&self, col_idx: usize) -> String {
         let base = &self.headers[col_idx];
-        let label = if self.sort_column == Some(col_idx) {
-            let dir = if matches!(self.sort_direction, SortDirection::Descending) {
-                "▼"
-            } else {
-                "▲"
-            };
-            format!("{} {}", base, dir)
-        } else {
-            base.clone()
+        let label = match self.sort_key_position(col_idx) {
+            Some(pos) => {
+                let key = &self.sort_keys[pos];
+                let dir = match key.direction {
+                    SortDirection::Descending => "▼",
+                    SortDirection::Ascending => "▲",
+                };
+                // Priorities only matter once there is more than one key.
+                let priority = if self.sort_keys.len() > 1 {
+                    (pos + 1).to_string()
+                } else {
+                    String::new()
+                };
+                let nulls = if key.nulls_last { "∅" } else { "" };
+                format!("{} {}{}{}", base, dir, priority, nulls)
+            }
+            None => base.clone(),
         };
         if self.groupby_keys.contains(&col_idx) {
             format!("{} [K]", label)
-        } else if let Some(func) = self.groupby_aggs.get(&col_idx) {
-            let sym = match func {
-                AggFunc::Sum => "Σ",
-                AggFunc::Mean => "μ",
-                AggFunc::Count => "#",
-                AggFunc::Min => "↓",
-                AggFunc::Max => "↑",
-            };
-            format!("{} [{}]", label, sym)
+        } else if let Some(funcs) = self.groupby_aggs.get(&col_idx) {
+            let syms: Vec<String> = funcs.iter().map(AggFunc::symbol).collect();
+            format!("{} [{}]", label, syms.join(" "))
         } else {
             label
         }
     }
 
-    pub fn compute_stats(&mut self, col: usize) -> ColumnStats {
-        if col >= self.headers.len() {
-            return ColumnStats::default();
-        }
-        let col_name = &self.headers[col];
-        let Ok(series) = self.view.column(col_name) else {
-            return ColumnStats::default();
-        };
-        let count = series.len();
-        let min = series
-            .min_reduce()
-            .ok()
-            .map(|s| s.value().to_string())
-            .unwrap_or_default();
-        let max = series
-            .max_reduce()
-            .ok()
-            .map(|s| s.value().to_string())
-            .unwrap_or_default();
-        let (mean, median) = series
-            .as_series()
-            .map(|s| (s.mean(), s.median()))
-            .unwrap_or((None, None));
-        ColumnStats {
-            count,
-            min,
-            max,
-            mean,
-            median,
-        }
+    /// Every column in display order, hidden ones included: pinned columns
+    /// first, then the chooser's order, then those it doesn't mention.
+    pub fn column_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = self
+            .column_layout
+            .order
+            .iter()
+            .filter_map(|name| self.headers.iter().position(|h| h == name))
+            .collect();
+        let rest: Vec<usize> = (0..self.headers.len())
+            .filter(|i| !order.contains(i))
+            .collect();
+        order.extend(rest);
+        order.sort_by_key(|&i| !self.is_column_pinned(i));
+        order
     }
 
-    pub fn toggle_groupby_key(&mut self) {
-        let col = self.state.selected_column().unwrap_or(0);
-        if let Some(pos) = self.groupby_keys.iter().position(|&k| k == col) {
-            self.groupby_keys.remove(pos);
-        } else {
-            self.groupby_keys.push(col);
-            self.groupby_aggs.remove(&col);
-        }
+    /// The columns the table renders, left to right.
+    pub fn display_columns(&self) -> Vec<usize> {
+        self.column_order()
+            .into_iter()
+            .filter(|&i| !self.is_column_hidden(i))
+            .collect()
     }
 
-    pub fn cycle_groupby_agg(&mut self) {
-        let col = self.state.selected_column().unwrap_or(0);
-        if self.groupby_keys.contains(&col) {
-            return;
-        };
-        let next = match self.groupby_aggs.get(&col) {
-            None => Some(AggFunc::Sum),
-            Some(AggFunc::Sum) => Some(AggFunc::Mean),
-            Some(AggFunc::Mean) => Some(AggFunc::Count),
-            Some(AggFunc::Count) => Some(AggFunc::Min),
-            Some(AggFunc::Min) => Some(AggFunc::Max),
-            Some(AggFunc::Max) => None,
-        };
-        match next {
-            Some(f) => {
-                self.groupby_aggs.insert(col, f);
-            }
-            None => {
-                self.groupby_aggs.remove(&col);
-            }
-        };
+    pub fn is_column_hidden(&self, col_idx: usize) -> bool {
+        self.column_layout.hidden.contains(&self.headers[col_idx])
     }
+
+    pub fn is_column_pinned(&self, col_idx: usize) -> bool {
+        self.column_layout.pinned.contains(&self.headers[col_idx])
+    }
+
+    pub fn select_next_column(&mut self) {
+        self.step_column(true);
+    }
+
+    pub fn select_previous_column(&mut self) {
+        self.step_column(false);
+    }
+
+    /// Move the cursor to the neighbouring shown column, skipping hidden ones.
+    fn step_column(&mut self, forward: bool) {
+        let shown = self.display_columns();
+        let Some(last) = shown.len().checked_sub(1) else {
+            return;
+        };
+        let pos = self
+            .state
+            .selected_column()
+            .and_then(|c| shown.iter().position(|&i| i == c));
+        let next = match pos {
+            Some(p) if forward => (p + 1).min(last),
+            Some(p) => p.saturating_sub(1),
+            None => 0,
+        };
+        self.state.select_column(Some(shown[next]));
+    }
+
+    /// If the cursor is on a hidden column, move it to the nearest shown one,
+    /// preferring the right.
+    pub fn ensure_column_shown(&mut self) {
+        let Some(current) = self.state.selected_column() else {
+            return;
+        };
+        let order = self.column_order();
+        let Some(pos) = order.iter().position(|&i| i == current) else {
+            self.state
+                .select_column(self.display_columns().first().copied());
+            return;
+        };
+        if !self.is_column_hidden(current) {
+            return;
+        }
+        let right = order[pos..].iter().find(|&&i| !self.is_column_hidden(i));
+        let left = order[..pos]
+            .iter()
+            .rev()
+            .find(|&&i| !self.is_column_hidden(i));
+        if let Some(&col) = right.or(left) {
+            self.state.select_column(Some(col));
+        }
+    }
+
+    /// Open the column chooser on the column under the cursor.
+    pub fn open_column_chooser(&mut self) {
+        let order = self.column_order();
+        let pos = self
+            .state
+            .selected_column()
+            .and_then(|c| order.iter().position(|&i| i == c));
+        self.column_chooser_state.select(pos.or(Some(0)));
+        self.mode = Mode::ColumnChooser;
+    }
+
+    /// The column highlighted in the chooser.
+    pub fn chooser_column(&self) -> Option<usize> {
+        self.column_order()
+            .get(self.column_chooser_state.selected()?)
+            .copied()
+    }
+
+    /// Show or hide the chooser's column. At least one column stays shown.
+    pub fn toggle_column_hidden(&mut self) {
+        let Some(idx) = self.chooser_column() else {
+            return;
+        };
+        let name = self.headers[idx].clone();
+        if let Some(pos) = self.column_layout.hidden.iter().position(|h| *h == name) {
+            self.column_layout.hidden.remove(pos);
+        } else if self.display_columns().len() > 1 {
+            self.column_layout.hidden.push(name);
+            self.ensure_column_shown();
+        } else {
+            self.status_message = Some(StatusMessage::Error(
+                "Can't hide the last shown column".to_string(),
+            ));
+        }
+    }
+
+    /// Pin or unpin the chooser's column, keeping the chooser on it as it moves.
+    pub fn toggle_column_pinned(&mut self) {
+        let Some(idx) = self.chooser_column() else {
+            return;
+        };
+        let name = self.headers[idx].clone();
+        let pinned = &mut self.column_layout.pinned;
+        match pinned.iter().position(|h| *h == name) {
+            Some(pos) => {
+                pinned.remove(pos);
+            }
+            None => pinned.push(name),
+        }
+        let pos = self.column_order().iter().position(|&i| i == idx);
+        self.column_chooser_state.select(pos);
+    }
+
+    /// Swap the chooser's column with its left or right neighbour. Pinned
+    /// columns only trade places with each other, as do unpinned ones.
+    pub fn move_column(&mut self, forward: bool) {
+        let Some(pos) = self.column_chooser_state.selected() else {
+            return;
+        };
+        let mut order = self.column_order();
+        let target = if forward {
+            pos + 1
+        } else if pos > 0 {
+            pos - 1
+        } else {
+            return;
+        };
+        let (Some(&idx), Some(&other)) = (order.get(pos), order.get(target)) else {
+            return;
+        };
+        if self.is_column_pinned(idx) != self.is_column_pinned(other) {
+            return;
+        }
+        order.swap(pos, target);
+        // Keep the place of columns this view doesn't have, for when it returns.
+        let mut names: Vec<String> = order.iter().map(|&i| self.headers[i].clone()).collect();
+        names.extend(
+            self.column_layout
+                .order
+                .iter()
+                .filter(|name| !self.headers.contains(name))
+                .cloned(),
+        );
+        self.column_layout.order = names;
+        self.column_chooser_state.select(Some(target));
+    }
+
+    /// Show every column again, in data order and unpinned.
+    pub fn reset_column_layout(&mut self) {
+        let idx = self.chooser_column();
+        self.column_layout = ColumnLayout::default();
+        self.column_chooser_state.select(idx);
+    }
+
+    /// How many leading shown columns stay in place: the pinned ones, or the
+    /// first `frozen_columns` if that is more.
+    pub fn frozen_count(&self) -> usize {
+        let shown = self.display_columns();
+        let pinned = shown
+            .iter()
+            .take_while(|&&i| self.is_column_pinned(i))
+            .count();
+        pinned.max(self.frozen_columns).min(shown.len())
+    }
+
+    /// Freeze the shown columns up to and including the cursor's, or unfreeze
+    /// them if they already are.
+    pub fn toggle_freeze(&mut self) {
+        let shown = self.display_columns();
+        let Some(pos) = self
+            .state
+            .selected_column()
+            .and_then(|c| shown.iter().position(|&i| i == c))
+        else {
+            return;
+        };
+        self.frozen_columns = if self.frozen_columns == pos + 1 {
+            0
+        } else {
+            pos + 1
+        };
+    }
+
+    /// The columns that fit in `width` cells with the width each gets, and how
+    /// many of them are frozen. Frozen columns come first; the others scroll to
+    /// keep the cursor's column in sight, and the last one may be cut short.
+    /// Nothing is frozen when the frozen columns alone fill the width.
+    pub fn fit_columns(&mut self, width: u16) -> (Vec<(usize, u16)>, usize) {
+        let width = width as usize;
+        let shown = self.display_columns();
+        let cost = |i: &usize| self.column_widths[*i] as usize + COLUMN_SPACING;
+        let mut frozen = self.frozen_count();
+        if shown[..frozen].iter().map(cost).sum::<usize>() >= width {
+            frozen = 0;
+        }
+        let (fixed, scrolling) = shown.split_at(frozen);
+        let room = width.saturating_sub(fixed.iter().map(cost).sum());
+
+        let cursor = self
+            .state
+            .selected_column()
+            .and_then(|c| scrolling.iter().position(|&i| i == c));
+        if let Some(pos) = cursor {
+            if pos < self.column_offset {
+                self.column_offset = pos;
+            }
+            while self.column_offset < pos
+                && scrolling[self.column_offset..=pos]
+                    .iter()
+                    .map(cost)
+                    .sum::<usize>()
+                    > room + COLUMN_SPACING
+            {
+                self.column_offset += 1;
+            }
+        }
+        self.column_offset = self.column_offset.min(scrolling.len().saturating_sub(1));
+
+        let mut left = width;
+        let mut fitted = Vec::new();
+        for &i in fixed.iter().chain(&scrolling[self.column_offset..]) {
+            if left == 0 {
+                break;
+            }
+            let w = (self.column_widths[i] as usize).min(left);
+            fitted.push((i, w as u16));
+            left = left.saturating_sub(w + COLUMN_SPACING);
+        }
+        (fitted, frozen)
+    }
+
+    pub fn compute_stats(&mut self, col: usize) -> ColumnStats {
+        if col >= self.headers.len() {
+            return ColumnStats::default();
+        }
+        let col_name = &self.headers[col];
+        let Some(series) = self.view_column(col_name) else {
+            return ColumnStats::default();
+        };
+        let count = series.len();
+        let min = series
+            .min_reduce()
+            .ok()
+            .map(|s| s.value().to_string())
+            .unwrap_or_default();
+        let max = series
+            .max_reduce()
+            .ok()
+            .map(|s| s.value().to_string())
+            .unwrap_or_default();
+        let (mean, median) = series
+            .as_series()
+            .map(|s| (s.mean(), s.median()))
+            .unwrap_or((None, None));
+        ColumnStats {
+            count,
+            min,
+            max,
+            mean,
+            median,
+        }
+    }
+
+    pub fn toggle_groupby_key(&mut self) {
+        let col = self.state.selected_column().unwrap_or(0);
+        if let Some(pos) = self.groupby_keys.iter().position(|&k| k == col) {
+            self.groupby_keys.remove(pos);
+        } else {
+            self.groupby_keys.push(col);
+            self.groupby_aggs.remove(&col);
+        }
+    }
+
+    /// Step the column's most recently added aggregation to the next function,
+    /// dropping it after the last one.
+    pub fn cycle_groupby_agg(&mut self) {
+        let col = self.state.selected_column().unwrap_or(0);
+        if self.groupby_keys.contains(&col) {
+            return;
+        };
+        let funcs = self.groupby_aggs.entry(col).or_default();
+        let start = match funcs.pop() {
+            None => 0,
+            Some(last) => AGG_CYCLE
+                .iter()
+                .position(|f| *f == last)
+                .map_or(0, |i| i + 1),
+        };
+        if let Some(next) = AGG_CYCLE[start.min(AGG_CYCLE.len())..]
+            .iter()
+            .find(|f| !funcs.contains(f))
+        {
+            funcs.push(next.clone());
+        }
+        if funcs.is_empty() {
+            self.groupby_aggs.remove(&col);
+        }
+    }
+
+    /// Add another aggregation to the column, so one column can produce
+    /// e.g. both `salary_mean` and `salary_p90`.
+    pub fn add_groupby_agg(&mut self) {
+        let col = self.state.selected_column().unwrap_or(0);
+        if self.groupby_keys.contains(&col) {
+            return;
+        };
+        let funcs = self.groupby_aggs.entry(col).or_default();
+        if let Some(next) = AGG_CYCLE.iter().find(|f| !funcs.contains(f)) {
+            funcs.push(next.clone());
+        }
+    }
+
     pub fn apply_groupby(&mut self) {
-        if self.groupby_keys.is_empty() || self.groupby_aggs.is_empty() {
+        if self.groupby_keys.is_empty() || self.groupby_aggs.is_empty() || self.pivot.is_some() {
             return;
         }
         let key_exprs: Vec<Expr> = self
@@ -452,25 +1404,15 @@ impl App {
             .iter()
             .map(|&i| col(&self.headers[i]))
             .collect();
-        let agg_exprs: Vec<Expr> = self
-            .groupby_aggs
-            .iter()
-            .map(|(i, func)| {
-                let name = &self.headers[*i];
-                match func {
-                    AggFunc::Sum => col(name).sum().alias(format!("{}_sum", name)),
-                    AggFunc::Mean => col(name).mean().alias(format!("{}_mean", name)),
-                    AggFunc::Count => col(name).count().alias(format!("{}_count", name)),
-                    AggFunc::Min => col(name).min().alias(format!("{}_min", name)),
-                    AggFunc::Max => col(name).max().alias(format!("{}_max", name)),
-                }
-            })
+        let mut agg_cols: Vec<(&usize, &Vec<AggFunc>)> = self.groupby_aggs.iter().collect();
+        agg_cols.sort_by_key(|(i, _)| **i);
+        let agg_exprs: Vec<Expr> = agg_cols
+            .into_iter()
+            .flat_map(|(i, funcs)| funcs.iter().map(|f| f.expr(&self.headers[*i])))
             .collect();
         let first_key = self.headers[self.groupby_keys[0]].clone();
         let result = self
-            .view
-            .clone()
-            .lazy()
+            .view_lazy()
             .group_by(key_exprs)
             .agg(agg_exprs)
             .sort([&first_key], SortMultipleOptions::default())
@@ -485,10 +1427,11 @@ impl App {
                 .map(|s| s.to_string())
                 .collect();
             self.column_widths = vec![DEFAULT_COLUMN_WIDTH; df.width()];
-            self.sort_column = None;
+            self.sort_keys = Vec::new();
             self.search_results = Vec::new();
             self.search_cursor = 0;
             self.view = df;
+            self.lazy_view = None;
             self.groupby_active = true;
             self.state.select(Some(0));
             self.state.select_column(Some(0));
@@ -503,12 +1446,10 @@ impl App {
 
         let counts: Vec<(String, usize)> = (|| {
             let s = self
-                .view
-                .column(&self.headers[col_idx])
-                .ok()?
+                .view_column(&self.headers[col_idx])?
                 .as_series()?
                 .clone();
-            let str_s = s.cast(&DataType::String).ok()?;
+            let str_s = to_display_strings(&s)?;
             let ca = str_s.str().ok()?.clone();
             let mut map: HashMap<String, usize> = HashMap::new();
             for v in ca.into_iter() {
@@ -550,9 +1491,9 @@ impl App {
 
     pub fn build_columns_profile(&mut self) {
         self.columns_profile = self
-            .view
-            .get_columns()
+            .headers
             .iter()
+            .filter_map(|name| self.view_column(name))
             .map(|col| {
                 let name = col.name().to_string();
                 let dtype = col.dtype().to_string();
@@ -587,95 +1528,811 @@ impl App {
         self.columns_view_state.select(Some(0));
     }
 
-    pub fn plot_type_label(&self) -> &str {
-        match self.plot_type {
-            PlotType::Line => "Line",
-            PlotType::Bar => "Bar",
-            PlotType::Histogram => "Histogram",
-        }
+    /// Write the current view (the group-by result when grouped) to the path in
+    /// `export_input` and report the outcome in the status bar. A diff's
+    /// changed-column lists are left out, as CSV can't hold them.
+    pub fn export_view(&mut self) {
+        let path = self.export_input.trim().to_string();
+        let result = self
+            .collect_view()
+            .map_err(|e| e.into())
+            .and_then(|mut df| {
+                if self.diff.is_some() {
+                    let _ = df.drop_in_place(DIFF_CHANGED_COLUMN);
+                }
+                write_dataframe(&mut df, &path).map(|_| df.height())
+            });
+        self.status_message = Some(match result {
+            Ok(rows) => StatusMessage::Info(format!("Wrote {} rows to {}", rows, path)),
+            Err(err) => StatusMessage::Error(format!("Export failed: {}", err)),
+        });
     }
 
-    pub fn clear_groupby(&mut self) {
-        self.view_offset = 0;
-        self.headers = self.saved_headers.clone();
-        self.column_widths = self.saved_column_widths.clone();
+    /// Run `sql_input` against the loaded or joined data (registered as `self`) and show
+    /// the result in place of it. Errors are reported in the status bar.
+    pub fn apply_sql(&mut self) {
+        let query = self.sql_input.trim().to_string();
+        let mut ctx = SQLContext::new();
+        let table = match (&self.join_view, &self.source) {
+            (Some(joined), _) => joined.clone().lazy(),
+            (None, Some(source)) => source.clone(),
+            (None, None) => self.df.clone().lazy(),
+        };
+        ctx.register(SQL_TABLE_NAME, table);
+        let df = match ctx.execute(&query).and_then(|lf| lf.collect()) {
+            Ok(df) => df,
+            Err(err) => {
+                self.status_message = Some(StatusMessage::Error(format!("SQL error: {}", err)));
+                return;
+            }
+        };
+        if self.groupby_active {
+            self.clear_groupby();
+        }
+        self.clear_pivot();
+        if self.sql_view.is_none() {
+            self.sql_saved_column_widths = self.column_widths.clone();
+        }
+        self.headers = df
+            .get_column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        self.column_widths = vec![DEFAULT_COLUMN_WIDTH; df.width()];
         self.groupby_keys = Vec::new();
         self.groupby_aggs = HashMap::new();
-        self.groupby_active = false;
-        self.sort_column = None;
+        self.filters = Vec::new();
+        self.filter_input = String::new();
+        self.sort_keys = Vec::new();
         self.search_results = Vec::new();
         self.search_cursor = 0;
+        self.sql_view = Some(df);
+        self.sql_query = query;
+        // Columns computed on the previous result carry over to the new one.
+        self.recompute_columns(DataLayer::Sql);
+        self.lazy_view = None;
+        self.lazy_window = None;
         self.update_filter();
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Drop the SQL result and go back to the loaded data.
+    pub fn clear_sql(&mut self) {
+        if self.sql_view.is_none() {
+            return;
+        }
+        if self.groupby_active {
+            self.clear_groupby();
+        }
+        self.clear_pivot();
+        self.sql_view = None;
+        self.sql_query = String::new();
+        self.headers = self
+            .join_view
+            .as_ref()
+            .unwrap_or(&self.df)
+            .get_column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        self.column_widths = self.sql_saved_column_widths.clone();
+        self.recompute_columns(DataLayer::Sql);
+        self.groupby_keys = Vec::new();
+        self.groupby_aggs = HashMap::new();
+        self.filters = Vec::new();
+        self.filter_input = String::new();
+        self.sort_keys = Vec::new();
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        self.update_filter();
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
+    }
 
-    fn make_app() -> App {
-        let df = df! {
-            "name" => ["Alice", "Bob", "Charlie"],
-            "age" => [30i64, 25, 35],
+    /// Add (or replace) a column computed from `compute_input`, e.g.
+    /// `margin = (revenue - cost) / revenue`. It is added to the data the view
+    /// is built from (the SQL result or joined data, when shown), so filters,
+    /// sort, group-by and export see it. Errors go to `compute_error`.
+    pub fn add_computed_column(&mut self) -> bool {
+        self.compute_error = None;
+        if self.groupby_active || self.pivot.is_some() {
+            self.compute_error = Some("clear the group-by or pivot first".to_string());
+            return false;
+        }
+        let input = self.compute_input.trim().to_string();
+        match self.compute_column(&input) {
+            Ok(col_idx) => {
+                self.computed_columns.push((input, self.data_layer()));
+                self.update_filter();
+                self.state.select_column(Some(col_idx));
+                true
+            }
+            Err(err) => {
+                self.compute_error = Some(err);
+                false
+            }
         }
-        .unwrap();
-        App::new(df, "test.csv".to_string())
     }
 
-    fn get_str(app: &App, col: &str, row: usize) -> String {
-        app.view
-            .column(col)
-            .unwrap()
-            .as_series()
-            .unwrap()
-            .cast(&DataType::String)
-            .unwrap()
-            .str()
-            .unwrap()
-            .get(row)
-            .unwrap_or("")
-            .to_string()
+    /// The frame the view is currently built from.
+    fn data_layer(&self) -> DataLayer {
+        if self.sql_view.is_some() {
+            DataLayer::Sql
+        } else if self.join_view.is_some() {
+            DataLayer::Joined
+        } else {
+            DataLayer::Loaded
+        }
     }
 
-    #[test]
-    fn test_update_search_finds_matches() {
-        let mut app = make_app();
-        app.search_query = "alice".to_string();
-        app.update_search();
-        assert_eq!(app.search_results, vec![0]);
+    /// Add the column `input` defines to the frame the view is built from and
+    /// return its header index.
+    fn compute_column(&mut self, input: &str) -> Result<usize, String> {
+        let schema = self.derived_base().unwrap_or(&self.df).schema().clone();
+        let (name, expr) = parse_computed_column(input, &schema)?;
+        let with_column = |df: &DataFrame| df.clone().lazy().with_column(expr.clone()).collect();
+        let result = if let Some(df) = &self.sql_view {
+            with_column(df).map(|df| self.sql_view = Some(df))
+        } else if let Some(df) = &self.join_view {
+            with_column(df).map(|df| self.join_view = Some(df))
+        } else {
+            // In lazy mode `df` only holds the schema; the source is checked
+            // against its schema rather than scanned.
+            let source = self
+                .source
+                .as_ref()
+                .map(|s| s.clone().with_column(expr.clone()));
+            let checked = match &source {
+                Some(lf) => lf.clone().collect_schema().map(|_| ()),
+                None => Ok(()),
+            };
+            checked.and_then(|_| with_column(&self.df)).map(|df| {
+                self.df = df;
+                self.source = source;
+            })
+        };
+        result.map_err(|err| err.to_string())?;
+        Ok(match self.headers.iter().position(|h| *h == name) {
+            Some(i) => i,
+            None => {
+                self.headers.push(name);
+                self.column_widths.push(DEFAULT_COLUMN_WIDTH);
+                self.headers.len() - 1
+            }
+        })
     }
 
-    #[test]
-    fn test_update_search_case_insensitive() {
-        let mut app = make_app();
-        app.search_query = "ALICE".to_string();
-        app.update_search();
-        assert_eq!(app.search_results, vec![0]);
+    /// Add the columns computed on `layer`, which the view no longer shows,
+    /// to the current frame. Those that no longer apply are dropped.
+    fn recompute_columns(&mut self, layer: DataLayer) {
+        let current = self.data_layer();
+        let mut dropped = Vec::new();
+        for (input, on) in std::mem::take(&mut self.computed_columns) {
+            if on != layer {
+                self.computed_columns.push((input, on));
+            } else if self.compute_column(&input).is_ok() {
+                self.computed_columns.push((input, current));
+            } else {
+                dropped.push(input);
+            }
+        }
+        if !dropped.is_empty() {
+            self.status_message = Some(StatusMessage::Info(format!(
+                "Dropped computed columns: {}",
+                dropped.join("; ")
+            )));
+        }
     }
 
-    #[test]
-    fn test_update_search_no_matches() {
-        let mut app = make_app();
-        app.search_query = "xyz".to_string();
-        app.update_search();
-        assert!(app.search_results.is_empty());
+    /// Load `join_input` and open the popup for picking the join keys. The key
+    /// defaults to the selected column and a file column of the same name.
+    pub fn load_join_file(&mut self) {
+        let path = self.join_input.trim().to_string();
+        let right = match load_dataframe(&path, &self.csv_options) {
+            Ok(df) => df,
+            Err(err) => {
+                self.status_message = Some(StatusMessage::Error(format!(
+                    "Could not load {}: {}",
+                    path, err
+                )));
+                return;
+            }
+        };
+        let left_key = self.state.selected_column().unwrap_or(0);
+        let right_key = self
+            .headers
+            .get(left_key)
+            .and_then(|name| right.get_column_index(name))
+            .unwrap_or(0);
+        self.join_setup = JoinSpec {
+            path,
+            keys: vec![(left_key, right_key)],
+            kind: self.join_setup.kind,
+        };
+        self.join_right = Some(right);
+        self.join_form_state.select(Some(0));
+        self.mode = Mode::JoinSetup;
     }
 
-    #[test]
-    fn test_update_filter_finds_matches() {
-        let mut app = make_app();
-        app.filters = vec![(0, "Bob".to_string())];
-        app.update_filter();
-        assert_eq!(app.view.height(), 1);
+    /// Change the selected field of the join popup to its next or previous value.
+    pub fn shift_join_field(&mut self, forward: bool) {
+        let step = |i: usize, n: usize| {
+            let n = n.max(1);
+            if forward {
+                (i + 1) % n
+            } else {
+                (i + n - 1) % n
+            }
+        };
+        let right_width = self.join_right.as_ref().map_or(0, |df| df.width());
+        let spec = &mut self.join_setup;
+        match self.join_form_state.selected() {
+            Some(row) if row < 2 * spec.keys.len() => {
+                let key = &mut spec.keys[row / 2];
+                if row % 2 == 0 {
+                    key.0 = step(key.0, self.headers.len());
+                } else {
+                    key.1 = step(key.1, right_width);
+                }
+            }
+            Some(row) if row == 2 * spec.keys.len() => spec.kind = spec.kind.cycled(forward),
+            _ => {}
+        }
     }
 
-    #[test]
-    fn test_autofit_uses_data_width() {
-        let mut app = make_app();
-        app.state.select_column(Some(0));
-        app.autofit_selected_column();
-        // "name" col: max("Alice"=5, "Bob"=3, "Charlie"=7) = 7, header "name" = 4
-        assert_eq!(app.column_widths[0], 7);
+    /// Add a key pair to the join: the view column after the last key, and a
+    /// file column of the same name if there is one.
+    pub fn add_join_key(&mut self) {
+        let Some(right) = &self.join_right else {
+            return;
+        };
+        let keys = &mut self.join_setup.keys;
+        let left = keys
+            .last()
+            .map_or(0, |&(l, _)| (l + 1) % self.headers.len().max(1));
+        let right_key = self
+            .headers
+            .get(left)
+            .and_then(|name| right.get_column_index(name))
+            .unwrap_or(0);
+        keys.push((left, right_key));
+        self.join_form_state.select(Some(2 * (keys.len() - 1)));
+    }
+
+    /// Drop the key pair under the cursor. The join keeps at least one.
+    pub fn remove_join_key(&mut self) {
+        let keys = &mut self.join_setup.keys;
+        let Some(row) = self.join_form_state.selected() else {
+            return;
+        };
+        if keys.len() > 1 && row < 2 * keys.len() {
+            keys.remove(row / 2);
+            self.join_form_state.select(Some(row.min(2 * keys.len())));
+        }
+    }
+
+    /// Join the current view with `join_right` and show the result in place of
+    /// the data, like a SQL query. Filters, sorting and group-by start over on
+    /// the joined columns; `clear_join` goes back to the loaded data.
+    pub fn apply_join(&mut self) {
+        let Some(right) = self.join_right.take() else {
+            return;
+        };
+        if self.groupby_active || self.pivot.is_some() {
+            self.status_message = Some(StatusMessage::Error(
+                "Clear the group-by or pivot before joining".to_string(),
+            ));
+            return;
+        }
+        let spec = self.join_setup.clone();
+        let keys: Vec<(String, String)> = spec
+            .keys
+            .iter()
+            .map(|&(l, r)| {
+                (
+                    self.headers[l].clone(),
+                    right.get_column_names()[r].to_string(),
+                )
+            })
+            .collect();
+        let joined = self.view_lazy().join(
+            right.lazy(),
+            keys.iter().map(|(l, _)| col(l)).collect::<Vec<_>>(),
+            keys.iter().map(|(_, r)| col(r)).collect::<Vec<_>>(),
+            JoinArgs::new(spec.kind.join_type()),
+        );
+        let df = match joined.collect() {
+            Ok(df) => df,
+            Err(err) => {
+                self.status_message = Some(StatusMessage::Error(format!("Join failed: {}", err)));
+                return;
+            }
+        };
+        if self.joins.is_empty() {
+            self.join_saved_column_widths = if self.sql_view.is_some() {
+                self.sql_saved_column_widths.clone()
+            } else {
+                self.column_widths.clone()
+            };
+        }
+        // Columns kept from the view keep their widths.
+        self.column_widths = df
+            .get_column_names()
+            .iter()
+            .map(|name| {
+                self.headers
+                    .iter()
+                    .position(|h| h == name.as_str())
+                    .map_or(DEFAULT_COLUMN_WIDTH, |i| self.column_widths[i])
+            })
+            .collect();
+        self.headers = df
+            .get_column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let file_name = std::path::Path::new(&spec.path)
+            .file_name()
+            .map_or(spec.path.clone(), |n| n.to_string_lossy().into_owned());
+        let on = keys
+            .iter()
+            .map(|(l, r)| format!("{} = {}", l, r))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.joins
+            .push(format!("{} {} on {}", spec.kind.label(), file_name, on));
+        self.join_view = Some(df);
+        self.sql_view = None;
+        self.sql_query = String::new();
+        // The join kept the columns computed on the SQL result.
+        for (_, layer) in &mut self.computed_columns {
+            if *layer == DataLayer::Sql {
+                *layer = DataLayer::Joined;
+            }
+        }
+        self.groupby_keys = Vec::new();
+        self.groupby_aggs = HashMap::new();
+        self.filters = Vec::new();
+        self.filter_input = String::new();
+        self.sort_keys = Vec::new();
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        self.lazy_view = None;
+        self.lazy_window = None;
+        self.update_filter();
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
+    }
+
+    /// Drop every join (and a SQL query over the joined data) and go back to
+    /// the loaded data.
+    pub fn clear_join(&mut self) {
+        if self.join_view.is_none() {
+            return;
+        }
+        self.clear_sql();
+        self.clear_pivot();
+        if self.groupby_active {
+            self.clear_groupby();
+        }
+        self.join_view = None;
+        self.joins = Vec::new();
+        self.headers = self
+            .df
+            .get_column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        self.column_widths = self.join_saved_column_widths.clone();
+        self.recompute_columns(DataLayer::Joined);
+        self.groupby_keys = Vec::new();
+        self.groupby_aggs = HashMap::new();
+        self.filters = Vec::new();
+        self.filter_input = String::new();
+        self.sort_keys = Vec::new();
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        self.update_filter();
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
+    }
+
+    pub fn snapshot(&self) -> ViewSnapshot {
+        ViewSnapshot {
+            df: self.df.clone(),
+            source: self.source.clone(),
+            computed_columns: self.computed_columns.clone(),
+            view: self.view.clone(),
+            lazy_view: self.lazy_view.clone(),
+            lazy_height: self.lazy_height,
+            headers: self.headers.clone(),
+            column_widths: self.column_widths.clone(),
+            filters: self.filters.clone(),
+            sort_keys: self.sort_keys.clone(),
+            groupby_keys: self.groupby_keys.clone(),
+            groupby_aggs: self.groupby_aggs.clone(),
+            groupby_active: self.groupby_active,
+            saved_headers: self.saved_headers.clone(),
+            saved_column_widths: self.saved_column_widths.clone(),
+            sql_view: self.sql_view.clone(),
+            sql_query: self.sql_query.clone(),
+            sql_saved_column_widths: self.sql_saved_column_widths.clone(),
+            join_view: self.join_view.clone(),
+            joins: self.joins.clone(),
+            join_saved_column_widths: self.join_saved_column_widths.clone(),
+            drill_stack: self.drill_stack.clone(),
+            pivot: self.pivot.clone(),
+            column_layout: self.column_layout.clone(),
+            frozen_columns: self.frozen_columns,
+            selected: self.state.selected(),
+            selected_column: self.state.selected_column(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: ViewSnapshot) {
+        self.df = snapshot.df;
+        self.source = snapshot.source;
+        self.computed_columns = snapshot.computed_columns;
+        self.view = snapshot.view;
+        self.lazy_view = snapshot.lazy_view;
+        self.lazy_height = snapshot.lazy_height;
+        self.lazy_window = None;
+        self.headers = snapshot.headers;
+        self.column_widths = snapshot.column_widths;
+        self.filters = snapshot.filters;
+        self.sort_keys = snapshot.sort_keys;
+        self.groupby_keys = snapshot.groupby_keys;
+        self.groupby_aggs = snapshot.groupby_aggs;
+        self.groupby_active = snapshot.groupby_active;
+        self.saved_headers = snapshot.saved_headers;
+        self.saved_column_widths = snapshot.saved_column_widths;
+        self.sql_view = snapshot.sql_view;
+        self.sql_query = snapshot.sql_query;
+        self.sql_saved_column_widths = snapshot.sql_saved_column_widths;
+        self.join_view = snapshot.join_view;
+        self.joins = snapshot.joins;
+        self.join_saved_column_widths = snapshot.join_saved_column_widths;
+        self.drill_stack = snapshot.drill_stack;
+        self.pivot = snapshot.pivot;
+        self.column_layout = snapshot.column_layout;
+        self.frozen_columns = snapshot.frozen_columns;
+        self.state.select(snapshot.selected);
+        self.state.select_column(snapshot.selected_column);
+        self.view_offset = 0;
+        self.filter_input = String::new();
+        self.filter_error = None;
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        // A snapshot taken while a filter was being typed holds rows filtered
+        // by it, so the rows are rebuilt from the restored filters. Grouped
+        // and pivoted views are results of their own and are kept as saved.
+        if !self.groupby_active && self.pivot.is_none() {
+            self.update_filter();
+        } else if !self.search_query.is_empty() {
+            self.update_search();
+        }
+    }
+
+    /// Push `before` onto the undo history if the view has changed since.
+    pub fn record_history(&mut self, before: ViewSnapshot) {
+        if before.same_view(&self.snapshot()) {
+            return;
+        }
+        self.undo_stack.push(before);
+        if self.undo_stack.len() > MAX_HISTORY {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.redo_stack.push(self.snapshot());
+        self.restore(snapshot);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(snapshot) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.undo_stack.push(self.snapshot());
+        self.restore(snapshot);
+        true
+    }
+
+    pub fn plot_type_label(&self) -> &str {
+        match self.plot_type {
+            PlotType::Line => "Line",
+            PlotType::Bar => "Bar",
+            PlotType::Histogram => "Histogram",
+        }
+    }
+
+    pub fn clear_groupby(&mut self) {
+        self.view_offset = 0;
+        self.headers = self.saved_headers.clone();
+        self.column_widths = self.saved_column_widths.clone();
+        self.groupby_keys = Vec::new();
+        self.groupby_aggs = HashMap::new();
+        self.groupby_active = false;
+        self.sort_keys = Vec::new();
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        self.update_filter();
+    }
+
+    /// Open the rows behind the selected group: the data as it was before the
+    /// group-by, with a filter per key column. `drill_up` returns to the
+    /// grouped view without recomputing it.
+    pub fn drill_down(&mut self) {
+        if !self.groupby_active {
+            return;
+        }
+        let Some(row) = self.state.selected() else {
+            return;
+        };
+        let mut key_filters = Vec::new();
+        let mut crumbs = Vec::new();
+        for &key in &self.groupby_keys {
+            let name = &self.saved_headers[key];
+            let Some(values) = self
+                .view
+                .column(name)
+                .and_then(|c| c.cast(&DataType::String))
+                .ok()
+            else {
+                return;
+            };
+            let (query, crumb) = match values.str().ok().and_then(|s| s.get(row)) {
+                None => ("is null".to_string(), format!("{}=∅", name)),
+                Some(v) => (
+                    format!("= \"{}\"", v.replace('"', "\"\"")),
+                    format!("{}={}", name, v),
+                ),
+            };
+            key_filters.push(Filter::new(key, query));
+            crumbs.push(crumb);
+        }
+        let grouped = self.snapshot();
+        self.filters.extend(key_filters);
+        self.clear_groupby();
+        self.drill_stack.push((crumbs.join(", "), grouped));
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
+    }
+
+    /// Open the pivot popup, with the selected column as the row key.
+    pub fn open_pivot_setup(&mut self) {
+        let n = self.headers.len();
+        if n < 3 {
+            self.status_message = Some(StatusMessage::Error(
+                "A pivot needs at least three columns".to_string(),
+            ));
+            return;
+        }
+        let spec = &mut self.pivot_setup;
+        spec.rows = self.state.selected_column().unwrap_or(0).min(n - 1);
+        if spec.columns >= n || spec.columns == spec.rows {
+            spec.columns = (spec.rows + 1) % n;
+        }
+        if spec.values >= n || spec.values == spec.rows || spec.values == spec.columns {
+            spec.values = (0..n)
+                .find(|i| *i != spec.rows && *i != spec.columns)
+                .unwrap_or(0);
+        }
+        self.pivot_form_state.select(Some(0));
+        self.mode = Mode::PivotSetup;
+    }
+
+    /// Change the selected field of the pivot popup to its next or previous value.
+    pub fn shift_pivot_field(&mut self, forward: bool) {
+        let n = self.headers.len().max(1);
+        let step = |i: usize| {
+            if forward {
+                (i + 1) % n
+            } else {
+                (i + n - 1) % n
+            }
+        };
+        let spec = &mut self.pivot_setup;
+        match self.pivot_form_state.selected() {
+            Some(0) => spec.rows = step(spec.rows),
+            Some(1) => spec.columns = step(spec.columns),
+            Some(2) => spec.values = step(spec.values),
+            Some(3) => spec.func = spec.func.cycled(forward),
+            Some(4) => spec.totals = !spec.totals,
+            _ => {}
+        }
+    }
+
+    /// Replace the view with the pivot table described by `pivot_setup`.
+    pub fn apply_pivot(&mut self) {
+        if self.groupby_active || self.pivot.is_some() {
+            return;
+        }
+        let spec = self.pivot_setup.clone();
+        let df = match self.pivot_frame(&spec) {
+            Ok(df) => df,
+            Err(err) => {
+                self.status_message = Some(StatusMessage::Error(format!("Pivot failed: {}", err)));
+                return;
+            }
+        };
+        self.view_offset = 0;
+        self.saved_headers = self.headers.clone();
+        self.saved_column_widths = self.column_widths.clone();
+        self.headers = df
+            .get_column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        self.column_widths = vec![DEFAULT_COLUMN_WIDTH; df.width()];
+        self.sort_keys = Vec::new();
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        self.view = df;
+        self.lazy_view = None;
+        self.pivot = Some(spec);
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
+    }
+
+    fn pivot_frame(&self, spec: &PivotSpec) -> Result<DataFrame, String> {
+        let [rows, columns, values] =
+            [spec.rows, spec.columns, spec.values].map(|i| self.headers[i].as_str());
+        if rows == columns || rows == values || columns == values {
+            return Err("rows, columns and values must be different columns".to_string());
+        }
+        let data = self
+            .view_lazy()
+            .select([col(rows), col(columns), col(values)])
+            .collect()
+            .map_err(|e| e.to_string())?;
+        let agg = spec.func.apply(col(values));
+        let wide = pivot_stable(
+            &data,
+            [columns],
+            Some([rows]),
+            Some([values]),
+            true,
+            Some(agg.clone()),
+            None,
+        )
+        .and_then(|df| df.sort([rows], SortMultipleOptions::default()))
+        .map_err(|e| e.to_string())?;
+        if !spec.totals {
+            return Ok(wide);
+        }
+
+        // Totals are aggregated from the data, so e.g. a mean total is the mean
+        // of all values rather than the mean of the cells.
+        let mut join_args = JoinArgs::new(JoinType::Left);
+        join_args.join_nulls = true;
+        let row_totals = data
+            .clone()
+            .lazy()
+            .group_by([col(rows)])
+            .agg([agg.clone().alias(PIVOT_TOTAL)]);
+        let with_totals = wide
+            .clone()
+            .lazy()
+            .join(row_totals, [col(rows)], [col(rows)], join_args)
+            .with_column(col(rows).cast(DataType::String));
+        let key = col(columns).cast(DataType::String).fill_null(lit("null"));
+        let mut cells = vec![lit(PIVOT_TOTAL).alias(rows)];
+        cells.extend(wide.get_column_names()[1..].iter().map(|name| {
+            spec.func
+                .apply(col(values).filter(key.clone().eq(lit(name.as_str()))))
+                .alias(name.as_str())
+        }));
+        cells.push(agg.alias(PIVOT_TOTAL));
+        let column_totals = data.lazy().select(cells);
+        let args = UnionArgs {
+            to_supertypes: true,
+            ..Default::default()
+        };
+        concat([with_totals, column_totals], args)
+            .and_then(|lf| lf.collect())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Leave the pivot table and go back to the data it was built from.
+    pub fn clear_pivot(&mut self) {
+        if self.pivot.take().is_none() {
+            return;
+        }
+        self.view_offset = 0;
+        self.headers = self.saved_headers.clone();
+        self.column_widths = self.saved_column_widths.clone();
+        self.sort_keys = Vec::new();
+        self.search_results = Vec::new();
+        self.search_cursor = 0;
+        self.update_filter();
+        self.state.select(Some(0));
+        self.state.select_column(Some(0));
+    }
+
+    /// Return to the grouped view the current rows were drilled into from.
+    pub fn drill_up(&mut self) -> bool {
+        let Some((_, grouped)) = self.drill_stack.last().cloned() else {
+            return false;
+        };
+        self.restore(grouped);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie"],
+            "age" => [30i64, 25, 35],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    fn get_str(app: &App, col: &str, row: usize) -> String {
+        app.view
+            .column(col)
+            .unwrap()
+            .as_series()
+            .unwrap()
+            .cast(&DataType::String)
+            .unwrap()
+            .str()
+            .unwrap()
+            .get(row)
+            .unwrap_or("")
+            .to_string()
+    }
+
+    #[test]
+    fn test_update_search_finds_matches() {
+        let mut app = make_app();
+        app.search_query = "alice".to_string();
+        app.update_search();
+        assert_eq!(app.search_results, vec![0]);
+    }
+
+    #[test]
+    fn test_update_search_case_insensitive() {
+        let mut app = make_app();
+        app.search_query = "ALICE".to_string();
+        app.update_search();
+        assert_eq!(app.search_results, vec![0]);
+    }
+
+    #[test]
+    fn test_update_search_no_matches() {
+        let mut app = make_app();
+        app.search_query = "xyz".to_string();
+        app.update_search();
+        assert!(app.search_results.is_empty());
+    }
+
+    #[test]
+    fn test_update_filter_finds_matches() {
+        let mut app = make_app();
+        app.filters = vec![Filter::new(0, "Bob")];
+        app.update_filter();
+        assert_eq!(app.view.height(), 1);
+    }
+
+    #[test]
+    fn test_autofit_uses_data_width() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.autofit_selected_column();
+        // "name" col: max("Alice"=5, "Bob"=3, "Charlie"=7) = 7, header "name" = 4
+        assert_eq!(app.column_widths[0], 7);
     }
 
     #[test]
@@ -727,7 +2384,7 @@ mod tests {
         .unwrap();
         let mut app = App::new(df, "test.csv".to_string());
         // Filter to zero rows then search — must not panic
-        app.filters = vec![(0, "zzznomatch".to_string())];
+        app.filters = vec![Filter::new(0, "zzznomatch")];
         app.update_filter();
         app.search_query = "alice".to_string();
         app.update_search();
@@ -741,7 +2398,7 @@ mod tests {
         }
         .unwrap();
         let mut app = App::new(df, "test.csv".to_string());
-        app.filters = vec![(0, "zzznomatch".to_string())];
+        app.filters = vec![Filter::new(0, "zzznomatch")];
         app.update_filter();
         // Should return default stats without panicking
         let stats = app.compute_stats(0);
@@ -751,7 +2408,7 @@ mod tests {
     #[test]
     fn test_filter_to_zero_rows() {
         let mut app = make_app();
-        app.filters = vec![(0, "zzznomatch".to_string())];
+        app.filters = vec![Filter::new(0, "zzznomatch")];
         app.update_filter();
         assert_eq!(app.view.height(), 0);
     }
@@ -843,42 +2500,174 @@ mod groupby_tests {
     }
 
     #[test]
-    fn test_clear_groupby_restores_view() {
+    fn test_drill_down_filters_group_rows() {
         let mut app = make_app();
-        app.state.select_column(Some(0));
-        app.toggle_groupby_key();
-        app.state.select_column(Some(1));
-        app.cycle_groupby_agg();
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(1, vec![AggFunc::Sum]);
         app.apply_groupby();
-        app.clear_groupby();
+        app.state.select(Some(0)); // eng
+        app.drill_down();
         assert!(!app.groupby_active);
-        assert_eq!(app.view.height(), 3);
-        assert_eq!(app.headers[0], "dept");
+        assert_eq!(app.headers, vec!["dept", "sal"]);
+        assert_eq!(app.view.height(), 2);
+        assert_eq!(app.filters, vec![Filter::new(0, "= \"eng\"")]);
+        assert_eq!(app.drill_stack[0].0, "dept=eng");
     }
-}
 
-#[cfg(test)]
-mod filter_expr_tests {
-    use super::*;
+    #[test]
+    fn test_drill_up_restores_grouped_view() {
+        let mut app = make_app();
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(1, vec![AggFunc::Sum]);
+        app.apply_groupby();
+        app.state.select(Some(1)); // hr
+        app.drill_down();
+        assert_eq!(app.view.height(), 1);
+        assert!(app.drill_up());
+        assert!(app.groupby_active);
+        assert!(app.drill_stack.is_empty());
+        assert!(app.filters.is_empty());
+        assert_eq!(app.headers, vec!["dept", "sal_sum"]);
+        assert_eq!(app.state.selected(), Some(1));
+        assert!(!app.drill_up());
+    }
 
-    fn make_app() -> App {
+    #[test]
+    fn test_drill_down_keeps_filters_and_null_keys() {
         let df = df! {
-            "name" => ["Alice", "Bob", "Charlie"],
-            "age"  => [18i64, 25, 30],
+            "dept" => [Some("eng"), None, None],
+            "sal"  => [100i64, 200, 150],
         }
         .unwrap();
-        App::new(df, "test.csv".to_string())
-    }
-
-    fn apply(app: &mut App, col_idx: usize, query: &str) -> usize {
-        app.filters = vec![(col_idx, query.to_string())];
+        let mut app = App::new(df, "test.csv".to_string());
+        app.filters = vec![Filter::new(1, "> 120")];
         app.update_filter();
-        app.view.height()
-    }
-
-    #[test]
-    fn test_gt() {
-        assert_eq!(apply(&mut make_app(), 1, "> 18"), 2); // Bob, Charlie
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(1, vec![AggFunc::Count]);
+        app.apply_groupby();
+        app.state.select(Some(0)); // the null group sorts first
+        app.drill_down();
+        assert_eq!(app.filters.len(), 2);
+        assert_eq!(app.filters[1].query, "is null");
+        assert_eq!(app.view.height(), 2);
+    }
+
+    #[test]
+    fn test_drill_down_escapes_quotes() {
+        let df = df! {
+            "title" => ["Bob's \"best\"", "plain"],
+            "n"     => [1i64, 2],
+        }
+        .unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(1, vec![AggFunc::Sum]);
+        app.apply_groupby();
+        app.state.select(Some(0));
+        app.drill_down();
+        assert_eq!(app.filters[0].query, r#"= "Bob's ""best""""#);
+        assert_eq!(app.view.height(), 1);
+        assert_eq!(app.view.column("n").unwrap().i64().unwrap().get(0), Some(1));
+    }
+
+    #[test]
+    fn test_multiple_aggs_per_column() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.toggle_groupby_key();
+        app.state.select_column(Some(1));
+        app.cycle_groupby_agg(); // Sum
+        app.cycle_groupby_agg(); // Mean
+        app.add_groupby_agg(); // Sum again, as a second aggregation
+        app.cycle_groupby_agg(); // Sum → Median (Mean is taken)
+        assert_eq!(app.header_label(1), "sal [μ x̃]");
+        app.apply_groupby();
+        assert_eq!(app.headers, vec!["dept", "sal_mean", "sal_median"]);
+    }
+
+    #[test]
+    fn test_rich_aggs() {
+        let mut app = make_app();
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(
+            1,
+            vec![
+                AggFunc::Std,
+                AggFunc::NUnique,
+                AggFunc::Quantile(50),
+                AggFunc::Quantile(90),
+                AggFunc::Concat,
+                AggFunc::Last,
+            ],
+        );
+        app.apply_groupby();
+        let eng = app.view.slice(0, 1);
+        let get = |name: &str| eng.column(name).unwrap().get(0).unwrap().to_string();
+        assert_eq!(get("sal_n_unique"), "2");
+        assert_eq!(get("sal_p50"), "150.0");
+        assert_eq!(get("sal_p90"), "190.0");
+        assert_eq!(get("sal_concat"), "\"100, 200\"");
+        assert_eq!(get("sal_last"), "200");
+        assert!(get("sal_std").starts_with("70.71"));
+    }
+
+    #[test]
+    fn test_cycle_past_last_agg_removes_it() {
+        let mut app = make_app();
+        app.state.select_column(Some(1));
+        for _ in 0..AGG_CYCLE.len() {
+            app.cycle_groupby_agg();
+        }
+        assert_eq!(app.groupby_aggs[&1], vec![AggFunc::Concat]);
+        app.cycle_groupby_agg();
+        assert!(app.groupby_aggs.is_empty());
+    }
+
+    #[test]
+    fn test_parse_agg_names() {
+        assert_eq!(AggFunc::parse("p75"), Some(AggFunc::Quantile(75)));
+        assert_eq!(AggFunc::parse("NUnique"), Some(AggFunc::NUnique));
+        assert_eq!(AggFunc::parse("p101"), None);
+        assert_eq!(AggFunc::parse("mode"), None);
+    }
+
+    #[test]
+    fn test_clear_groupby_restores_view() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.toggle_groupby_key();
+        app.state.select_column(Some(1));
+        app.cycle_groupby_agg();
+        app.apply_groupby();
+        app.clear_groupby();
+        assert!(!app.groupby_active);
+        assert_eq!(app.view.height(), 3);
+        assert_eq!(app.headers[0], "dept");
+    }
+}
+
+#[cfg(test)]
+mod filter_expr_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie"],
+            "age"  => [18i64, 25, 30],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    fn apply(app: &mut App, col_idx: usize, query: &str) -> usize {
+        app.filters = vec![Filter::new(col_idx, query)];
+        app.update_filter();
+        app.view.height()
+    }
+
+    #[test]
+    fn test_gt() {
+        assert_eq!(apply(&mut make_app(), 1, "> 18"), 2); // Bob, Charlie
     }
 
     #[test]
@@ -912,15 +2701,32 @@ mod filter_expr_tests {
     }
 
     #[test]
-    fn test_non_numeric_value_falls_back_to_substring() {
-        // "> abc" can't parse as f64, so falls back to substring — no match
+    fn test_non_numeric_value_compares_as_text() {
+        // "abc" isn't a number, so the ages are compared as strings, and
+        // digits sort before letters.
         assert_eq!(apply(&mut make_app(), 1, "> abc"), 0);
+        assert_eq!(apply(&mut make_app(), 1, "< abc"), 3);
     }
 
     #[test]
     fn test_spaces_around_operator_and_value() {
         assert_eq!(apply(&mut make_app(), 1, "  >  18  "), 2);
     }
+
+    #[test]
+    fn test_or_across_values() {
+        assert_eq!(apply(&mut make_app(), 0, "= Alice or = Bob"), 2);
+    }
+
+    #[test]
+    fn test_invalid_filter_sets_error() {
+        let mut app = make_app();
+        app.state.select_column(Some(1));
+        app.filter_input = "> 18 and".to_string();
+        app.update_filter();
+        assert_eq!(app.filter_error.as_deref(), Some("expected a condition"));
+        assert_eq!(app.view.height(), 3);
+    }
 }
 
 #[cfg(test)]
@@ -956,3 +2762,1071 @@ mod plot_tests {
         assert!(x_is_categorical, "string x: should be categorical");
     }
 }
+
+#[cfg(test)]
+mod lazy_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie"],
+            "age"  => [30i64, 25, 35],
+        }
+        .unwrap();
+        App::new_lazy(df.lazy(), "test.parquet".to_string()).unwrap()
+    }
+
+    fn visible_names(app: &mut App) -> Vec<String> {
+        let rows = app.visible_rows(0, app.row_count());
+        rows.column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.unwrap_or("").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_new_lazy_reads_schema_only() {
+        let app = make_app();
+        assert_eq!(app.headers, vec!["name", "age"]);
+        assert_eq!(app.df.height(), 0);
+        assert_eq!(app.row_count(), 3);
+        assert!(app.has_data());
+        assert_eq!(app.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_lazy_filter_updates_row_count() {
+        let mut app = make_app();
+        app.filters = vec![Filter::new(1, "> 26")];
+        app.update_filter();
+        assert_eq!(app.row_count(), 2);
+        assert_eq!(visible_names(&mut app), vec!["Alice", "Charlie"]);
+    }
+
+    #[test]
+    fn test_lazy_filter_preview_counts_on_commit() {
+        let df = df! { "n" => (0..1000i64).collect::<Vec<_>>() }.unwrap();
+        let mut app = App::new_lazy(df.lazy(), "test.parquet".to_string()).unwrap();
+        app.filter_input = ">= 100".to_string();
+        app.preview_filter();
+        assert_eq!(app.row_count(), FILTER_PREVIEW_ROWS);
+        let window = app.visible_rows(5, 2);
+        assert_eq!(window.column("n").unwrap().i64().unwrap().get(0), Some(105));
+
+        app.update_filter();
+        assert_eq!(app.row_count(), 900);
+    }
+
+    #[test]
+    fn test_lazy_sort_by_column() {
+        let mut app = make_app();
+        app.state.select_column(Some(1));
+        app.sort_by_column();
+        app.sort_by_column();
+        assert_eq!(visible_names(&mut app), vec!["Charlie", "Alice", "Bob"]);
+    }
+
+    #[test]
+    fn test_lazy_visible_rows_window() {
+        let mut app = make_app();
+        let window = app.visible_rows(1, 1);
+        assert_eq!(window.height(), 1);
+        assert_eq!(
+            window.column("name").unwrap().str().unwrap().get(0),
+            Some("Bob")
+        );
+    }
+
+    #[test]
+    fn test_lazy_groupby_and_clear() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.toggle_groupby_key();
+        app.state.select_column(Some(1));
+        app.cycle_groupby_agg();
+        app.apply_groupby();
+        assert!(app.lazy_view.is_none());
+        assert_eq!(app.row_count(), 3);
+        app.clear_groupby();
+        assert!(app.lazy_view.is_some());
+        assert_eq!(app.row_count(), 3);
+    }
+}
+
+#[cfg(test)]
+mod display_tests {
+    use super::*;
+
+    #[test]
+    fn test_display_strings_formats_lists() {
+        let inner = [
+            Series::new("".into(), [1i64, 2]),
+            Series::new("".into(), [3i64]),
+        ];
+        let s = Series::new("tags".into(), inner);
+        let out = to_display_strings(&s).unwrap();
+        assert_eq!(out.str().unwrap().get(0), Some("[1, 2]"));
+        assert_eq!(out.str().unwrap().get(1), Some("[3]"));
+    }
+
+    #[test]
+    fn test_search_in_list_column() {
+        let inner = [
+            Series::new("".into(), ["red", "blue"]),
+            Series::new("".into(), ["green"]),
+        ];
+        let df = DataFrame::new(vec![Series::new("tags".into(), inner).into()]).unwrap();
+        let mut app = App::new(df, "test.json".to_string());
+        app.search_query = "green".to_string();
+        app.update_search();
+        assert_eq!(app.search_results, vec![1]);
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+
+    #[test]
+    fn test_export_view_writes_filtered_rows() {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie"],
+            "age"  => [30i64, 25, 35],
+        }
+        .unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.filters = vec![Filter::new(1, "> 26")];
+        app.update_filter();
+        let path = std::env::temp_dir().join("iron_sight_export_view.parquet");
+        app.export_input = path.to_string_lossy().into_owned();
+        app.export_view();
+        assert!(matches!(app.status_message, Some(StatusMessage::Info(_))));
+        let written = ParquetReader::new(std::fs::File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        assert_eq!(written.height(), 2);
+    }
+
+    #[test]
+    fn test_export_view_reports_errors() {
+        let df = df! { "a" => [1i64] }.unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.export_input = "out.xlsx".to_string();
+        app.export_view();
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+    }
+}
+
+#[cfg(test)]
+mod sql_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "dept" => ["eng", "eng", "hr"],
+            "age"  => [30i64, 41, 35],
+            "sal"  => [100i64, 200, 150],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    #[test]
+    fn test_apply_sql_replaces_view() {
+        let mut app = make_app();
+        app.sql_input =
+            "SELECT dept, avg(sal) AS avg_sal FROM self WHERE age > 31 GROUP BY dept ORDER BY dept"
+                .to_string();
+        app.apply_sql();
+        assert!(app.status_message.is_none());
+        assert_eq!(app.headers, vec!["dept", "avg_sal"]);
+        assert_eq!(app.column_widths.len(), 2);
+        assert_eq!(app.view.height(), 2);
+    }
+
+    #[test]
+    fn test_filter_applies_to_sql_result() {
+        let mut app = make_app();
+        app.sql_input = "SELECT dept, sal FROM self".to_string();
+        app.apply_sql();
+        app.filters = vec![Filter::new(1, "> 120")];
+        app.update_filter();
+        assert_eq!(app.view.height(), 2);
+        assert_eq!(app.view.width(), 2);
+    }
+
+    #[test]
+    fn test_clear_sql_restores_data() {
+        let mut app = make_app();
+        app.sql_input = "SELECT sal FROM self LIMIT 1".to_string();
+        app.apply_sql();
+        app.clear_sql();
+        assert!(app.sql_view.is_none());
+        assert_eq!(app.headers, vec!["dept", "age", "sal"]);
+        assert_eq!(app.column_widths.len(), 3);
+        assert_eq!(app.view.height(), 3);
+    }
+
+    #[test]
+    fn test_invalid_sql_keeps_view() {
+        let mut app = make_app();
+        app.sql_input = "SELEC nope".to_string();
+        app.apply_sql();
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+        assert!(app.sql_view.is_none());
+        assert_eq!(app.view.height(), 3);
+    }
+}
+
+#[cfg(test)]
+mod filter_list_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie", "Dana"],
+            "age"  => [18i64, 25, 30, 41],
+        }
+        .unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.filters = vec![Filter::new(1, "> 20"), Filter::new(0, "a")];
+        app.update_filter();
+        app
+    }
+
+    #[test]
+    fn test_disabled_filter_is_skipped() {
+        let mut app = make_app();
+        assert_eq!(app.view.height(), 2); // Charlie, Dana
+        app.filters[1].enabled = false;
+        app.update_filter();
+        assert_eq!(app.view.height(), 3);
+    }
+
+    #[test]
+    fn test_inverted_filter() {
+        let mut app = make_app();
+        app.filters[1].inverted = true;
+        app.update_filter();
+        assert_eq!(app.view.height(), 1); // Bob
+    }
+
+    #[test]
+    fn test_filter_impacts() {
+        let mut app = make_app();
+        app.build_filter_impacts();
+        // "a" alone already drops Alice; `> 20` alone keeps Bob.
+        assert_eq!(app.filter_impacts, vec![0, 1]);
+        app.filters[1].enabled = false;
+        app.update_filter();
+        app.build_filter_impacts();
+        assert_eq!(app.filter_impacts, vec![1, 1]);
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "dept" => ["eng", "eng", "hr"],
+            "sal"  => [100i64, 200, 150],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    #[test]
+    fn test_undo_redo_filter_and_sort() {
+        let mut app = make_app();
+        let before = app.snapshot();
+        app.filters.push(Filter::new(1, "> 120"));
+        app.update_filter();
+        app.record_history(before);
+        let before = app.snapshot();
+        app.state.select_column(Some(1));
+        app.sort_by_column();
+        app.record_history(before);
+
+        assert!(app.undo());
+        assert!(app.sort_keys.is_empty());
+        assert_eq!(app.view.height(), 2);
+        assert!(app.undo());
+        assert!(app.filters.is_empty());
+        assert_eq!(app.view.height(), 3);
+        assert!(!app.undo());
+
+        assert!(app.redo());
+        assert_eq!(app.view.height(), 2);
+        assert!(app.redo());
+        assert_eq!(
+            app.sort_keys,
+            vec![SortKey::new(1, SortDirection::Ascending)]
+        );
+        assert!(!app.redo());
+    }
+
+    #[test]
+    fn test_undo_groupby_restores_columns_and_cursor() {
+        let mut app = make_app();
+        app.column_widths[1] = 42;
+        app.state.select(Some(2));
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(1, vec![AggFunc::Sum]);
+        let before = app.snapshot();
+        app.apply_groupby();
+        app.record_history(before);
+        assert!(app.groupby_active);
+
+        assert!(app.undo());
+        assert!(!app.groupby_active);
+        assert_eq!(app.headers, vec!["dept", "sal"]);
+        assert_eq!(app.column_widths[1], 42);
+        assert_eq!(app.state.selected(), Some(2));
+        assert_eq!(app.view.height(), 3);
+    }
+
+    #[test]
+    fn test_cursor_moves_are_not_recorded() {
+        let mut app = make_app();
+        let before = app.snapshot();
+        app.state.select(Some(1));
+        app.record_history(before);
+        assert!(!app.undo());
+    }
+
+    #[test]
+    fn test_new_change_clears_redo() {
+        let mut app = make_app();
+        let before = app.snapshot();
+        app.autofit_all_columns();
+        app.record_history(before);
+        app.undo();
+        let before = app.snapshot();
+        app.filters.push(Filter::new(0, "hr"));
+        app.update_filter();
+        app.record_history(before);
+        assert!(!app.redo());
+    }
+}
+
+#[cfg(test)]
+mod sort_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "dept" => [Some("hr"), Some("eng"), None, Some("eng")],
+            "sal"  => [100i64, 200, 150, 300],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    fn sals(app: &App) -> Vec<Option<i64>> {
+        app.view
+            .column("sal")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_secondary_key() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.sort_by_column();
+        app.state.select_column(Some(1));
+        app.add_sort_column();
+        app.add_sort_column(); // second press flips it to descending
+        assert_eq!(sals(&app), vec![Some(150), Some(300), Some(200), Some(100)]);
+        assert_eq!(app.header_label(0), "dept ▲1");
+        assert_eq!(app.header_label(1), "sal ▼2");
+    }
+
+    #[test]
+    fn test_nulls_last_and_remove() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.sort_by_column();
+        app.toggle_sort_nulls();
+        assert_eq!(sals(&app), vec![Some(200), Some(300), Some(100), Some(150)]);
+        assert_eq!(app.header_label(0), "dept ▲∅");
+        app.remove_sort_column();
+        assert!(app.sort_keys.is_empty());
+        assert_eq!(sals(&app), vec![Some(100), Some(200), Some(150), Some(300)]);
+    }
+
+    #[test]
+    fn test_sort_replaces_keys() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.add_sort_column();
+        app.state.select_column(Some(1));
+        app.add_sort_column();
+        app.sort_by_column(); // `s` on an existing key only flips it
+        assert_eq!(app.sort_keys.len(), 2);
+        app.remove_sort_column();
+        app.state.select_column(Some(1));
+        app.sort_by_column(); // `s` on a column that isn't a key starts over
+        assert_eq!(
+            app.sort_keys,
+            vec![SortKey::new(1, SortDirection::Ascending)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod pivot_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "dept" => ["eng", "hr", "eng", "eng", "hr"],
+            "year" => [2025i64, 2025, 2026, 2026, 2026],
+            "sal"  => [100i64, 80, 200, 300, 90],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    fn pivot(app: &mut App, func: AggFunc, totals: bool) {
+        app.pivot_setup = PivotSpec {
+            rows: 0,
+            columns: 1,
+            values: 2,
+            func,
+            totals,
+        };
+        app.apply_pivot();
+    }
+
+    fn get_dept(app: &App) -> Vec<String> {
+        app.view
+            .column("dept")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.unwrap_or_default().to_string())
+            .collect()
+    }
+
+    fn column(app: &App, name: &str) -> Vec<Option<i64>> {
+        let s = app
+            .view
+            .column(name)
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap();
+        s.i64().unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn test_pivot_wide_table() {
+        let mut app = make_app();
+        pivot(&mut app, AggFunc::Sum, false);
+        assert_eq!(app.headers, vec!["dept", "2025", "2026"]);
+        assert_eq!(column(&app, "2025"), vec![Some(100), Some(80)]);
+        assert_eq!(column(&app, "2026"), vec![Some(500), Some(90)]);
+        assert_eq!(app.column_widths.len(), 3);
+    }
+
+    #[test]
+    fn test_pivot_totals() {
+        let mut app = make_app();
+        pivot(&mut app, AggFunc::Sum, true);
+        assert_eq!(app.headers, vec!["dept", "2025", "2026", "Total"]);
+        assert_eq!(app.view.height(), 3);
+        assert_eq!(column(&app, "Total"), vec![Some(600), Some(170), Some(770)]);
+        assert_eq!(column(&app, "2026"), vec![Some(500), Some(90), Some(590)]);
+        let label = app.view.column("dept").unwrap().get(2).unwrap();
+        assert_eq!(label.get_str(), Some(PIVOT_TOTAL));
+    }
+
+    #[test]
+    fn test_pivot_sort_and_clear() {
+        let mut app = make_app();
+        pivot(&mut app, AggFunc::Count, true);
+        app.state.select_column(Some(2));
+        app.sort_by_column();
+        app.sort_by_column(); // descending
+        assert_eq!(column(&app, "2026"), vec![Some(2), Some(1), Some(3)]);
+        app.state.select_column(Some(3));
+        app.sort_by_column(); // ascending on the totals column
+        assert_eq!(column(&app, PIVOT_TOTAL), vec![Some(2), Some(3), Some(5)]);
+        assert_eq!(get_dept(&app), vec!["hr", "eng", PIVOT_TOTAL]);
+        app.sort_keys.clear();
+        app.apply_sort();
+        assert_eq!(app.headers.len(), 4); // still pivoted
+        app.clear_pivot();
+        assert!(app.pivot.is_none());
+        assert_eq!(app.headers, vec!["dept", "year", "sal"]);
+        assert_eq!(app.view.height(), 5);
+    }
+
+    #[test]
+    fn test_pivot_uses_filtered_view_and_undo() {
+        let mut app = make_app();
+        app.filters = vec![Filter::new(2, "> 85")];
+        app.update_filter();
+        let before = app.snapshot();
+        pivot(&mut app, AggFunc::Sum, false);
+        app.record_history(before);
+        assert_eq!(column(&app, "2025"), vec![Some(100), None]);
+        assert!(app.undo());
+        assert!(app.pivot.is_none());
+        assert_eq!(app.headers, vec!["dept", "year", "sal"]);
+    }
+
+    #[test]
+    fn test_pivot_needs_distinct_columns() {
+        let mut app = make_app();
+        app.pivot_setup.columns = 0;
+        app.apply_pivot();
+        assert!(app.pivot.is_none());
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+    }
+
+    #[test]
+    fn test_pivot_setup_fields() {
+        let mut app = make_app();
+        app.state.select_column(Some(1));
+        app.open_pivot_setup();
+        assert_eq!(
+            (
+                app.pivot_setup.rows,
+                app.pivot_setup.columns,
+                app.pivot_setup.values
+            ),
+            (1, 2, 0)
+        );
+        app.pivot_form_state.select(Some(3));
+        app.shift_pivot_field(false);
+        assert_eq!(app.pivot_setup.func, AggFunc::Concat);
+        app.pivot_form_state.select(Some(4));
+        app.shift_pivot_field(true);
+        assert!(app.pivot_setup.totals);
+    }
+}
+
+#[cfg(test)]
+mod join_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie", "Dana"],
+            "dept_id" => [1i64, 2, 1, 3],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    fn depts() -> DataFrame {
+        df! {
+            "id" => [1i64, 2],
+            "dept" => ["eng", "hr"],
+        }
+        .unwrap()
+    }
+
+    fn join(app: &mut App, kind: JoinKind) {
+        app.join_setup = JoinSpec {
+            path: "data/depts.csv".to_string(),
+            keys: vec![(1, 0)],
+            kind,
+        };
+        app.join_right = Some(depts());
+        app.apply_join();
+    }
+
+    #[test]
+    fn test_left_join_adds_columns() {
+        let mut app = make_app();
+        app.column_widths[0] = 30;
+        join(&mut app, JoinKind::Left);
+        assert!(app.status_message.is_none());
+        assert_eq!(app.headers, vec!["name", "dept_id", "dept"]);
+        assert_eq!(app.column_widths, vec![30, 15, 15]);
+        assert_eq!(app.view.height(), 4);
+        assert_eq!(app.view.column("dept").unwrap().null_count(), 1);
+        assert_eq!(app.joins, vec!["left depts.csv on dept_id = id"]);
+        assert!(app.join_right.is_none());
+    }
+
+    #[test]
+    fn test_join_kinds() {
+        for (kind, rows, width) in [
+            (JoinKind::Inner, 3, 3),
+            (JoinKind::Anti, 1, 2),
+            (JoinKind::Semi, 3, 2),
+        ] {
+            let mut app = make_app();
+            join(&mut app, kind);
+            assert_eq!(
+                (app.view.height(), app.view.width()),
+                (rows, width),
+                "{:?}",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_joined_columns_and_clear() {
+        let mut app = make_app();
+        join(&mut app, JoinKind::Inner);
+        app.filters = vec![Filter::new(2, "= hr")];
+        app.update_filter();
+        assert_eq!(app.view.height(), 1);
+        app.clear_join();
+        assert!(app.joins.is_empty());
+        assert_eq!(app.headers, vec!["name", "dept_id"]);
+        assert_eq!(app.view.height(), 4);
+    }
+
+    #[test]
+    fn test_join_uses_filtered_view_and_sql_sees_it() {
+        let mut app = make_app();
+        app.filters = vec![Filter::new(0, "starts with A")];
+        app.update_filter();
+        join(&mut app, JoinKind::Left);
+        assert_eq!(app.view.height(), 1);
+        app.sql_input = "SELECT dept FROM self".to_string();
+        app.apply_sql();
+        assert_eq!(app.headers, vec!["dept"]);
+        app.clear_sql();
+        assert_eq!(app.headers, vec!["name", "dept_id", "dept"]);
+    }
+
+    #[test]
+    fn test_join_key_type_mismatch() {
+        let mut app = make_app();
+        app.join_setup.keys = vec![(0, 0)]; // name (str) against id (i64)
+        app.join_right = Some(depts());
+        app.apply_join();
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+        assert!(app.joins.is_empty());
+    }
+
+    #[test]
+    fn test_load_join_file_picks_matching_key() {
+        let dir = std::env::temp_dir().join("iron_sight_join_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lookup.csv");
+        std::fs::write(&path, "label,dept_id\nx,1\n").unwrap();
+        let mut app = make_app();
+        app.state.select_column(Some(1));
+        app.join_input = path.to_string_lossy().into_owned();
+        app.load_join_file();
+        assert!(matches!(app.mode, Mode::JoinSetup));
+        assert_eq!(app.join_setup.keys, vec![(1, 1)]);
+        app.join_input = "missing.csv".to_string();
+        app.load_join_file();
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+    }
+
+    #[test]
+    fn test_join_on_two_keys() {
+        let df = df! {
+            "region" => ["eu", "eu", "us"],
+            "year" => [2025i64, 2026, 2026],
+            "sales" => [10i64, 20, 30],
+        }
+        .unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.join_right = Some(
+            df! {
+                "year" => [2026i64, 2026],
+                "region" => ["eu", "us"],
+                "target" => [25i64, 35],
+            }
+            .unwrap(),
+        );
+        app.join_setup = JoinSpec {
+            path: "targets.csv".to_string(),
+            keys: vec![(0, 1)],
+            kind: JoinKind::Inner,
+        };
+        app.join_form_state.select(Some(0));
+        app.add_join_key(); // year = year
+        assert_eq!(app.join_setup.keys, vec![(0, 1), (1, 0)]);
+        assert_eq!(app.join_setup.fields().len(), 5);
+        app.apply_join();
+        assert_eq!(app.view.height(), 2);
+        assert_eq!(
+            app.joins,
+            vec!["inner targets.csv on region = region, year = year"]
+        );
+    }
+
+    #[test]
+    fn test_remove_join_key() {
+        let mut app = make_app();
+        app.join_right = Some(depts());
+        app.add_join_key();
+        assert_eq!(app.join_setup.keys.len(), 2);
+        app.remove_join_key();
+        assert_eq!(app.join_setup.keys.len(), 1);
+        app.remove_join_key(); // the last pair stays
+        assert_eq!(app.join_setup.keys.len(), 1);
+    }
+
+    #[test]
+    fn test_join_file_uses_csv_options() {
+        let dir = std::env::temp_dir().join("iron_sight_join_csv_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lookup.csv");
+        std::fs::write(&path, "id;dept\n1;eng\n").unwrap();
+        let mut app = make_app();
+        app.csv_options.delimiter = Some(b';');
+        app.join_input = path.to_string_lossy().into_owned();
+        app.load_join_file();
+        assert_eq!(app.join_right.unwrap().width(), 2);
+    }
+}
+
+#[cfg(test)]
+mod compute_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "item" => ["a", "b", "c"],
+            "revenue" => [200i64, 50, 100],
+            "cost" => [150i64, 60, 100],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    fn compute(app: &mut App, input: &str) -> bool {
+        app.compute_input = input.to_string();
+        app.add_computed_column()
+    }
+
+    #[test]
+    fn test_computed_column_is_appended() {
+        let mut app = make_app();
+        assert!(compute(&mut app, "margin = (revenue - cost) / revenue"));
+        assert_eq!(app.headers, vec!["item", "revenue", "cost", "margin"]);
+        assert_eq!(app.column_widths.len(), 4);
+        assert_eq!(app.state.selected_column(), Some(3));
+        assert_eq!(app.view.width(), 4);
+        assert_eq!(
+            app.computed_columns,
+            vec![(
+                "margin = (revenue - cost) / revenue".to_string(),
+                DataLayer::Loaded
+            )]
+        );
+    }
+
+    #[test]
+    fn test_filter_sort_and_group_on_computed_column() {
+        let mut app = make_app();
+        compute(&mut app, "profit = revenue - cost");
+        app.filters = vec![Filter::new(3, ">= 0")];
+        app.update_filter();
+        assert_eq!(app.view.height(), 2);
+        app.sort_keys = vec![SortKey::new(3, SortDirection::Descending)];
+        app.apply_sort();
+        let items: Vec<_> = app
+            .view
+            .column("item")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(items, vec![Some("a"), Some("c")]);
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(3, vec![AggFunc::Sum]);
+        app.apply_groupby();
+        assert_eq!(app.headers, vec!["item", "profit_sum"]);
+    }
+
+    #[test]
+    fn test_replacing_a_column_and_undo() {
+        let mut app = make_app();
+        let before = app.snapshot();
+        compute(&mut app, "cost = cost * 2");
+        app.record_history(before);
+        assert_eq!(app.headers.len(), 3);
+        let cost = app.view.column("cost").unwrap().i64().unwrap().get(0);
+        assert_eq!(cost, Some(300));
+        assert!(app.undo());
+        let cost = app.view.column("cost").unwrap().i64().unwrap().get(0);
+        assert_eq!(cost, Some(150));
+        assert!(app.computed_columns.is_empty());
+        app.update_filter();
+        assert_eq!(
+            app.view.column("cost").unwrap().i64().unwrap().get(0),
+            Some(150)
+        );
+    }
+
+    #[test]
+    fn test_computed_column_errors() {
+        let mut app = make_app();
+        assert!(!compute(&mut app, "x = price * 2"));
+        assert_eq!(
+            app.compute_error.as_deref(),
+            Some("no column named `price`")
+        );
+        assert!(!compute(&mut app, "x = item * 2"));
+        assert!(app.compute_error.is_some());
+        assert_eq!(app.headers.len(), 3);
+    }
+
+    #[test]
+    fn test_computed_column_in_lazy_mode() {
+        let df = df! { "a" => [1i64, 2, 3] }.unwrap();
+        let mut app = App::new_lazy(df.lazy(), "test.parquet".to_string()).unwrap();
+        assert!(compute(&mut app, "b = a * 10"));
+        let b = app.collect_view().unwrap();
+        let b: Vec<_> = b.column("b").unwrap().i64().unwrap().into_iter().collect();
+        assert_eq!(b, vec![Some(10), Some(20), Some(30)]);
+        assert!(!compute(&mut app, "c = upper(a)"));
+    }
+
+    #[test]
+    fn test_computed_columns_outlive_sql() {
+        let mut app = make_app();
+        compute(&mut app, "cost = cost * 2");
+        app.sql_input = "SELECT * FROM self WHERE revenue > 60".to_string();
+        app.apply_sql();
+        assert!(compute(&mut app, "profit = revenue - cost"));
+        app.clear_sql();
+        assert_eq!(app.headers, vec!["item", "revenue", "cost", "profit"]);
+        assert_eq!(app.view.height(), 3);
+        let profit = app.view.column("profit").unwrap().i64().unwrap();
+        assert_eq!(profit.get(0), Some(-100)); // cost was doubled once
+        assert_eq!(
+            app.computed_columns,
+            vec![
+                ("cost = cost * 2".to_string(), DataLayer::Loaded),
+                ("profit = revenue - cost".to_string(), DataLayer::Loaded),
+            ]
+        );
+
+        app.sql_input = "SELECT revenue AS rev FROM self".to_string();
+        app.apply_sql();
+        assert!(compute(&mut app, "double = rev * 2"));
+        app.clear_sql();
+        assert!(!app.headers.contains(&"double".to_string()));
+        assert_eq!(app.computed_columns.len(), 2);
+        assert_eq!(
+            app.status_message,
+            Some(StatusMessage::Info(
+                "Dropped computed columns: double = rev * 2".to_string()
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod column_layout_tests {
+    use super::*;
+
+    fn make_app() -> App {
+        let df = df! {
+            "id" => [1i64, 2, 3],
+            "name" => ["a", "b", "c"],
+            "age" => [30i64, 25, 35],
+            "city" => ["x", "y", "z"],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    /// Point the chooser at the column called `name`.
+    fn choose(app: &mut App, name: &str) {
+        let idx = app.headers.iter().position(|h| h == name).unwrap();
+        let pos = app.column_order().iter().position(|&i| i == idx);
+        app.column_chooser_state.select(pos);
+    }
+
+    fn shown_names(app: &App) -> Vec<&str> {
+        app.display_columns()
+            .iter()
+            .map(|&i| app.headers[i].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_hide_and_show() {
+        let mut app = make_app();
+        choose(&mut app, "name");
+        app.toggle_column_hidden();
+        assert_eq!(shown_names(&app), vec!["id", "age", "city"]);
+        app.toggle_column_hidden();
+        assert_eq!(shown_names(&app), vec!["id", "name", "age", "city"]);
+    }
+
+    #[test]
+    fn test_last_column_stays_shown() {
+        let mut app = make_app();
+        for name in ["id", "name", "age", "city"] {
+            choose(&mut app, name);
+            app.toggle_column_hidden();
+        }
+        assert_eq!(shown_names(&app), vec!["city"]);
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+    }
+
+    #[test]
+    fn test_cursor_skips_hidden_columns() {
+        let mut app = make_app();
+        app.state.select_column(Some(1));
+        choose(&mut app, "name");
+        app.toggle_column_hidden();
+        assert_eq!(app.state.selected_column(), Some(2));
+        app.select_previous_column();
+        assert_eq!(app.state.selected_column(), Some(0));
+        app.select_next_column();
+        assert_eq!(app.state.selected_column(), Some(2));
+    }
+
+    #[test]
+    fn test_move_columns() {
+        let mut app = make_app();
+        choose(&mut app, "city");
+        app.move_column(false);
+        app.move_column(false);
+        assert_eq!(shown_names(&app), vec!["id", "city", "name", "age"]);
+        assert_eq!(app.column_chooser_state.selected(), Some(1));
+        app.select_next_column();
+        assert_eq!(app.state.selected_column(), Some(3));
+    }
+
+    #[test]
+    fn test_pinned_columns_lead() {
+        let mut app = make_app();
+        choose(&mut app, "city");
+        app.toggle_column_pinned();
+        assert_eq!(shown_names(&app), vec!["city", "id", "name", "age"]);
+        assert_eq!(app.chooser_column(), Some(3));
+        // An unpinned column can't move ahead of a pinned one.
+        choose(&mut app, "id");
+        app.move_column(false);
+        assert_eq!(shown_names(&app), vec!["city", "id", "name", "age"]);
+        app.reset_column_layout();
+        assert_eq!(shown_names(&app), vec!["id", "name", "age", "city"]);
+    }
+
+    #[test]
+    fn test_hidden_columns_can_be_filtered() {
+        let mut app = make_app();
+        choose(&mut app, "age");
+        app.toggle_column_hidden();
+        app.filters.push(Filter::new(2, "> 28"));
+        app.update_filter();
+        assert_eq!(app.row_count(), 2);
+        assert_eq!(shown_names(&app), vec!["id", "name", "city"]);
+    }
+
+    #[test]
+    fn test_layout_follows_names_and_undo() {
+        let mut app = make_app();
+        let before = app.snapshot();
+        choose(&mut app, "name");
+        app.toggle_column_hidden();
+        app.record_history(before);
+        app.groupby_keys = vec![0];
+        app.groupby_aggs.insert(2, vec![AggFunc::Sum]);
+        app.apply_groupby();
+        assert_eq!(shown_names(&app), vec!["id", "age_sum"]);
+        app.clear_groupby();
+        assert_eq!(shown_names(&app), vec!["id", "age", "city"]);
+        assert!(app.undo());
+        assert_eq!(shown_names(&app), vec!["id", "name", "age", "city"]);
+    }
+}
+
+#[cfg(test)]
+mod frozen_column_tests {
+    use super::*;
+
+    /// Six columns, each 10 cells wide.
+    fn make_app() -> App {
+        let df = df! {
+            "id" => [1i64],
+            "a" => [1i64],
+            "b" => [1i64],
+            "c" => [1i64],
+            "d" => [1i64],
+            "e" => [1i64],
+        }
+        .unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.column_widths = vec![10; 6];
+        app
+    }
+
+    fn fitted_columns(app: &mut App, width: u16) -> Vec<usize> {
+        app.fit_columns(width).0.iter().map(|&(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_columns_scroll_to_the_cursor() {
+        let mut app = make_app();
+        // Room for three whole columns.
+        assert_eq!(fitted_columns(&mut app, 32), vec![0, 1, 2]);
+        app.state.select_column(Some(4));
+        assert_eq!(fitted_columns(&mut app, 32), vec![2, 3, 4]);
+        app.state.select_column(Some(1));
+        assert_eq!(fitted_columns(&mut app, 32), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_frozen_columns_stay_in_place() {
+        let mut app = make_app();
+        app.state.select_column(Some(0));
+        app.toggle_freeze();
+        assert_eq!(app.frozen_columns, 1);
+        app.state.select_column(Some(5));
+        let (fitted, frozen) = app.fit_columns(32);
+        assert_eq!(frozen, 1);
+        assert_eq!(
+            fitted.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+            vec![0, 4, 5]
+        );
+        app.state.select_column(Some(0));
+        app.toggle_freeze();
+        assert_eq!(app.frozen_columns, 0);
+    }
+
+    #[test]
+    fn test_pinned_columns_are_frozen() {
+        let mut app = make_app();
+        app.column_layout.pinned = vec!["c".to_string()];
+        assert_eq!(app.frozen_count(), 1);
+        app.state.select_column(Some(5));
+        assert_eq!(fitted_columns(&mut app, 32), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_last_column_is_cut_short() {
+        let mut app = make_app();
+        let (fitted, _) = app.fit_columns(25);
+        assert_eq!(fitted, vec![(0, 10), (1, 10), (2, 3)]);
+    }
+
+    #[test]
+    fn test_nothing_frozen_without_room() {
+        let mut app = make_app();
+        app.frozen_columns = 3;
+        app.state.select_column(Some(5));
+        let (fitted, frozen) = app.fit_columns(25);
+        assert_eq!(frozen, 0);
+        assert_eq!(fitted.last().map(|&(i, _)| i), Some(5));
+    }
+}
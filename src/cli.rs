@@ -0,0 +1,402 @@
+use crate::app::AggFunc;
+use crate::loader::STDIN_PATH;
+use clap::{CommandFactory, Parser, ValueEnum};
+use polars::prelude::DataType;
+use std::io::IsTerminal;
+
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Config {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Files or glob patterns to open, stacked into one table. Use `-` (or pipe
+    /// data in) to read from stdin
+    #[arg(value_name = "FILE")]
+    pub paths: Vec<String>,
+
+    /// Scan the file lazily instead of loading it into memory
+    #[arg(long)]
+    pub lazy: bool,
+
+    /// Add a `__source_file` column naming the file each row came from
+    #[arg(long)]
+    pub source_column: bool,
+
+    /// Keep the first N columns in place while scrolling right
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub freeze: usize,
+
+    #[command(flatten)]
+    pub csv: CsvOptions,
+
+    #[command(flatten)]
+    pub query: QueryOptions,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Compare two files row by row, matching rows on key columns
+    Diff(DiffOptions),
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct DiffOptions {
+    /// The older file
+    pub old: String,
+
+    /// The newer file
+    pub new: String,
+
+    /// Column identifying a row in both files, e.g. `id` (repeatable or comma-separated)
+    #[arg(
+        long,
+        short = 'k',
+        value_name = "COL",
+        required = true,
+        value_delimiter = ','
+    )]
+    pub key: Vec<String>,
+
+    #[command(flatten)]
+    pub csv: CsvOptions,
+}
+
+/// CSV reader options. They are ignored for other formats.
+#[derive(Debug, Clone, Default, clap::Args)]
+#[command(next_help_heading = "CSV options")]
+pub struct CsvOptions {
+    /// Field delimiter, e.g. `;` or `\t` [default: detected from the header line]
+    #[arg(long, short = 'd', value_parser = parse_byte)]
+    pub delimiter: Option<u8>,
+
+    /// The first row is data, not column names
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Lines to skip before the header
+    #[arg(long, default_value_t = 0)]
+    pub skip_rows: usize,
+
+    /// Comma-separated values to read as null, e.g. `NA,-,null`
+    #[arg(long, value_delimiter = ',')]
+    pub null_values: Vec<String>,
+
+    /// Rows used to infer column types; 0 reads every row [default: 100]
+    #[arg(long)]
+    pub infer_schema_length: Option<usize>,
+
+    /// Text encoding of the file
+    #[arg(long, value_enum, default_value_t)]
+    pub encoding: Encoding,
+
+    /// Quote character [default: `"`]
+    #[arg(long, value_parser = parse_byte)]
+    pub quote_char: Option<u8>,
+
+    /// Skip lines starting with this prefix, e.g. `#`
+    #[arg(long)]
+    pub comment_prefix: Option<String>,
+
+    /// Override a column type, e.g. `--dtype zip=str --dtype amount=f64` (repeatable)
+    #[arg(long = "dtype", value_name = "NAME=TYPE", value_parser = parse_dtype_override)]
+    pub dtypes: Vec<(String, DataType)>,
+}
+
+/// Operations applied without starting the TUI. Giving any of them runs
+/// iron-sight headless.
+#[derive(Debug, Clone, Default, clap::Args)]
+#[command(next_help_heading = "Headless options")]
+pub struct QueryOptions {
+    /// Add a computed column before anything else, e.g.
+    /// `margin = (revenue - cost) / revenue` (repeatable)
+    #[arg(long = "compute", value_name = "NAME = EXPR")]
+    pub computes: Vec<String>,
+
+    /// Filter a column with the `f` syntax, e.g. `age:> 30` (repeatable)
+    #[arg(long = "filter", value_name = "COL:QUERY", value_parser = parse_filter)]
+    pub filters: Vec<(String, String)>,
+
+    /// Sort by a column, e.g. `age:desc`; repeat for secondary keys. With
+    /// `--group-by` this sorts the result
+    #[arg(long = "sort", value_name = "COL[:asc|desc]", value_parser = parse_sort)]
+    pub sorts: Vec<(String, bool)>,
+
+    /// Group by a column (repeatable)
+    #[arg(long = "group-by", value_name = "COL")]
+    pub group_by: Vec<String>,
+
+    /// Aggregate a column of the group-by, e.g. `salary:mean` (repeatable, also
+    /// per column). Functions: sum, mean, median, count, n_unique, null_count,
+    /// min, max, std, var, first, last, concat and percentiles like p90
+    #[arg(long = "agg", value_name = "COL:FUNC", value_parser = parse_agg)]
+    pub aggs: Vec<(String, AggFunc)>,
+
+    /// Write the result to a file, format chosen by extension [default: CSV to stdout]
+    #[arg(long, short = 'o', value_name = "PATH")]
+    pub output: Option<String>,
+}
+
+impl QueryOptions {
+    pub fn is_headless(&self) -> bool {
+        !self.computes.is_empty()
+            || !self.filters.is_empty()
+            || !self.sorts.is_empty()
+            || !self.group_by.is_empty()
+            || !self.aggs.is_empty()
+            || self.output.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// UTF-8, replacing invalid bytes with �
+    Utf8Lossy,
+    /// ISO-8859-1, transcoded to UTF-8 before parsing
+    #[value(alias = "iso-8859-1")]
+    Latin1,
+}
+
+impl Config {
+    pub fn new(args: impl Iterator<Item = String>) -> Result<Config, clap::Error> {
+        let mut config = Config::try_parse_from(args)?;
+        if config.command.is_some() {
+            return Ok(config);
+        }
+        // With no path, read from a pipe (`psql ... | iron-sight`).
+        if config.paths.is_empty() {
+            if std::io::stdin().is_terminal() {
+                return Err(Config::command().error(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    "Please provide a file path, or pipe data into stdin",
+                ));
+            }
+            config.paths.push(STDIN_PATH.to_string());
+        }
+        if config.query.group_by.is_empty() != config.query.aggs.is_empty() {
+            return Err(Config::command().error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "`--group-by` and `--agg` must be used together",
+            ));
+        }
+        if config.paths.len() > 1 && config.reads_stdin() {
+            return Err(Config::command().error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "`-` (stdin) can't be combined with other files",
+            ));
+        }
+        Ok(config)
+    }
+
+    pub fn reads_stdin(&self) -> bool {
+        self.paths.iter().any(|p| p == STDIN_PATH)
+    }
+}
+
+/// Parse a single-byte character, accepting `\t` and `tab` for tabs.
+fn parse_byte(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("expected a single ASCII character, got `{}`", s)),
+    }
+}
+
+fn parse_dtype_override(s: &str) -> Result<(String, DataType), String> {
+    let (name, dtype) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected NAME=TYPE, got `{}`", s))?;
+    Ok((name.to_string(), parse_dtype(dtype)?))
+}
+
+fn parse_filter(s: &str) -> Result<(String, String), String> {
+    let (name, query) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected COL:QUERY, got `{}`", s))?;
+    Ok((name.to_string(), query.to_string()))
+}
+
+/// Parse `COL`, `COL:asc` or `COL:desc` into the column and whether it's descending.
+fn parse_sort(s: &str) -> Result<(String, bool), String> {
+    match s.rsplit_once(':') {
+        Some((name, dir)) if dir.eq_ignore_ascii_case("asc") => Ok((name.to_string(), false)),
+        Some((name, dir)) if dir.eq_ignore_ascii_case("desc") => Ok((name.to_string(), true)),
+        _ => Ok((s.to_string(), false)),
+    }
+}
+
+fn parse_agg(s: &str) -> Result<(String, AggFunc), String> {
+    let (name, func) = s
+        .rsplit_once(':')
+        .ok_or_else(|| format!("expected COL:FUNC, got `{}`", s))?;
+    let func =
+        AggFunc::parse(func).ok_or_else(|| format!("unknown aggregation `{}`", func.trim()))?;
+    Ok((name.to_string(), func))
+}
+
+/// Map a user-facing type name to a polars dtype.
+pub fn parse_dtype(s: &str) -> Result<DataType, String> {
+    Ok(match s.trim().to_lowercase().as_str() {
+        "str" | "string" | "utf8" => DataType::String,
+        "int" | "i64" | "int64" => DataType::Int64,
+        "i32" | "int32" => DataType::Int32,
+        "u64" | "uint64" => DataType::UInt64,
+        "u32" | "uint32" => DataType::UInt32,
+        "float" | "f64" | "float64" => DataType::Float64,
+        "f32" | "float32" => DataType::Float32,
+        "bool" | "boolean" => DataType::Boolean,
+        "date" => DataType::Date,
+        "datetime" => DataType::Datetime(polars::prelude::TimeUnit::Microseconds, None),
+        other => return Err(format!("unknown type `{}`", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Config, clap::Error> {
+        Config::try_parse_from(std::iter::once("iron-sight").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_diff_command() {
+        let config = parse(&["diff", "old.parquet", "new.parquet", "--key", "id,day"]).unwrap();
+        let Some(Command::Diff(opts)) = config.command else {
+            panic!("expected the diff command");
+        };
+        assert_eq!(
+            (opts.old.as_str(), opts.new.as_str()),
+            ("old.parquet", "new.parquet")
+        );
+        assert_eq!(opts.key, vec!["id", "day"]);
+        assert!(config.paths.is_empty());
+        assert!(parse(&["diff", "old.csv", "new.csv"]).is_err()); // --key is required
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = parse(&["data.csv"]).unwrap();
+        assert_eq!(config.paths, vec!["data.csv"]);
+        assert!(!config.lazy);
+        assert_eq!(config.freeze, 0);
+        assert_eq!(config.csv.delimiter, None);
+        assert!(!config.csv.no_header);
+        assert_eq!(config.csv.encoding, Encoding::Utf8);
+    }
+
+    #[test]
+    fn test_reader_options() {
+        let config = parse(&[
+            "data.csv",
+            "--delimiter",
+            ";",
+            "--no-header",
+            "--skip-rows",
+            "2",
+            "--null-values",
+            "NA,-",
+            "--encoding",
+            "latin1",
+            "--quote-char",
+            "'",
+            "--comment-prefix",
+            "#",
+            "--dtype",
+            "zip=str",
+            "--dtype",
+            "amount=f64",
+        ])
+        .unwrap();
+        let csv = config.csv;
+        assert_eq!(csv.delimiter, Some(b';'));
+        assert!(csv.no_header);
+        assert_eq!(csv.skip_rows, 2);
+        assert_eq!(csv.null_values, vec!["NA", "-"]);
+        assert_eq!(csv.encoding, Encoding::Latin1);
+        assert_eq!(csv.quote_char, Some(b'\''));
+        assert_eq!(csv.comment_prefix.as_deref(), Some("#"));
+        assert_eq!(
+            csv.dtypes,
+            vec![
+                ("zip".to_string(), DataType::String),
+                ("amount".to_string(), DataType::Float64)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_paths() {
+        let config = parse(&["a.parquet", "b.parquet", "--source-column"]).unwrap();
+        assert_eq!(config.paths, vec!["a.parquet", "b.parquet"]);
+        assert!(config.source_column);
+    }
+
+    #[test]
+    fn test_freeze() {
+        let config = parse(&["wide.csv", "--freeze", "2"]).unwrap();
+        assert_eq!(config.freeze, 2);
+    }
+
+    #[test]
+    fn test_tab_delimiter() {
+        let config = parse(&["data.csv", "-d", "\\t"]).unwrap();
+        assert_eq!(config.csv.delimiter, Some(b'\t'));
+    }
+
+    #[test]
+    fn test_query_options() {
+        let config = parse(&[
+            "data.csv",
+            "--filter",
+            "age:> 30",
+            "--filter",
+            "dept:= eng",
+            "--sort",
+            "age:desc",
+            "--sort",
+            "name",
+            "--group-by",
+            "dept",
+            "--agg",
+            "salary:mean",
+            "-o",
+            "out.parquet",
+        ])
+        .unwrap();
+        let query = config.query;
+        assert!(query.is_headless());
+        assert_eq!(
+            query.filters,
+            vec![
+                ("age".to_string(), "> 30".to_string()),
+                ("dept".to_string(), "= eng".to_string())
+            ]
+        );
+        assert_eq!(
+            query.sorts,
+            vec![("age".to_string(), true), ("name".to_string(), false)]
+        );
+        assert_eq!(query.group_by, vec!["dept"]);
+        assert_eq!(query.aggs, vec![("salary".to_string(), AggFunc::Mean)]);
+        assert_eq!(query.output.as_deref(), Some("out.parquet"));
+    }
+
+    #[test]
+    fn test_sort_without_direction() {
+        let config = parse(&["data.csv", "--sort", "time:12"]).unwrap();
+        assert_eq!(config.query.sorts, vec![("time:12".to_string(), false)]);
+        assert!(!parse(&["data.csv"]).unwrap().query.is_headless());
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(parse(&["data.csv", "--delimiter", ";;"]).is_err());
+        assert!(parse(&["data.csv", "--dtype", "zip"]).is_err());
+        assert!(parse(&["data.csv", "--dtype", "zip=decimal128"]).is_err());
+        assert!(parse(&["data.csv", "--filter", "age"]).is_err());
+        assert!(parse(&["data.csv", "--agg", "salary:mode"]).is_err());
+    }
+}
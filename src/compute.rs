@@ -0,0 +1,470 @@
+//! Computed columns: `name = expression`, compiled into a polars `Expr`.
+//!
+//! Expressions combine columns and literals with `+ - * / %`, comparisons and
+//! functions, called either as `upper(name)` or as methods: `date.year()`.
+
+use polars::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Quoted(String),
+    Ident(String),
+    Column(String), // `backticked name`
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+    Assign,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            _ if ch.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.' || c == '_') {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.replace('_', "")));
+            }
+            '\'' | '"' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote stands for the quote itself, as in filters.
+                        Some(c) if c == ch && chars.next_if_eq(&ch).is_some() => text.push(c),
+                        Some(c) if c == ch => break,
+                        Some(c) => text.push(c),
+                        None => return Err(format!("missing closing {}", ch)),
+                    }
+                }
+                tokens.push(if ch == '`' {
+                    Token::Column(text)
+                } else {
+                    Token::Quoted(text)
+                });
+            }
+            '(' | ')' | ',' | '.' | '+' | '-' | '*' | '/' | '%' => {
+                chars.next();
+                tokens.push(match ch {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    '.' => Token::Dot,
+                    '+' => Token::Op("+"),
+                    '-' => Token::Op("-"),
+                    '*' => Token::Op("*"),
+                    '/' => Token::Op("/"),
+                    _ => Token::Op("%"),
+                });
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let eq = chars.next_if_eq(&'=').is_some();
+                tokens.push(match (ch, eq) {
+                    ('=', false) => Token::Assign,
+                    ('=', true) => Token::Op("=="),
+                    ('!', true) => Token::Op("!="),
+                    ('<', true) => Token::Op("<="),
+                    ('<', false) => Token::Op("<"),
+                    ('>', true) => Token::Op(">="),
+                    ('>', false) => Token::Op(">"),
+                    _ => return Err("unexpected !".to_string()),
+                });
+            }
+            _ if ch.is_alphabetic() || ch == '_' => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return Err(format!("unexpected {}", ch)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    schema: &'a Schema,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!("expected {}", what))
+        }
+    }
+
+    fn op(&mut self, ops: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.additive()?;
+        let Some(op) = self.op(&["==", "!=", "<", "<=", ">", ">="]) else {
+            return Ok(lhs);
+        };
+        let rhs = self.additive()?;
+        Ok(match op {
+            "==" => lhs.eq(rhs),
+            "!=" => lhs.neq(rhs),
+            "<" => lhs.lt(rhs),
+            "<=" => lhs.lt_eq(rhs),
+            ">" => lhs.gt(rhs),
+            _ => lhs.gt_eq(rhs),
+        })
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(op) = self.op(&["+", "-"]) {
+            let rhs = self.term()?;
+            lhs = if op == "+" { lhs + rhs } else { lhs - rhs };
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.op(&["*", "/", "%"]) {
+            let rhs = self.unary()?;
+            lhs = match op {
+                "*" => lhs * rhs,
+                // `/` always divides exactly, so `1 / 2` on integers is 0.5.
+                "/" => binary_expr(lhs, Operator::TrueDivide, rhs),
+                _ => lhs % rhs,
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.op(&["-"]).is_some() {
+            return Ok(lit(0) - self.unary()?);
+        }
+        self.postfix()
+    }
+
+    /// Method calls: `x.f(a)` is `f(x, a)`.
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        while self.eat(&Token::Dot) {
+            let Some(Token::Ident(name)) = self.next() else {
+                return Err("expected a function name after .".to_string());
+            };
+            let mut args = vec![expr];
+            if self.eat(&Token::LParen) {
+                args.extend(self.args()?);
+            }
+            expr = call(&name, args)?;
+        }
+        Ok(expr)
+    }
+
+    /// Arguments after `(`, up to and including `)`.
+    fn args(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        if self.eat(&Token::RParen) {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.comparison()?);
+            if self.eat(&Token::RParen) {
+                return Ok(args);
+            }
+            self.expect(&Token::Comma, ", or )")?;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) if n.contains('.') => n
+                .parse::<f64>()
+                .map(lit)
+                .map_err(|_| format!("`{}` is not a number", n)),
+            Some(Token::Number(n)) => n
+                .parse::<i64>()
+                .map(lit)
+                .map_err(|_| format!("`{}` is not a number", n)),
+            Some(Token::Quoted(s)) => Ok(lit(s)),
+            Some(Token::Column(name)) => self.column(name),
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let args = self.args()?;
+                call(&name, args)
+            }
+            Some(Token::Ident(name)) => match name.to_lowercase().as_str() {
+                _ if self.schema.contains(&name) => Ok(col(name.as_str())),
+                "true" => Ok(lit(true)),
+                "false" => Ok(lit(false)),
+                "null" => Ok(lit(NULL)),
+                _ => self.column(name),
+            },
+            Some(Token::LParen) => {
+                let expr = self.comparison()?;
+                self.expect(&Token::RParen, "closing )")?;
+                Ok(expr)
+            }
+            Some(token) => Err(format!("unexpected {}", describe(&token))),
+            None => Err("expected a value".to_string()),
+        }
+    }
+
+    fn column(&self, name: String) -> Result<Expr, String> {
+        if self.schema.contains(&name) {
+            Ok(col(name.as_str()))
+        } else {
+            Err(format!("no column named `{}`", name))
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Op(op) => op.to_string(),
+        Token::RParen => ")".to_string(),
+        Token::Comma => ",".to_string(),
+        Token::Dot => ".".to_string(),
+        Token::Assign => "=".to_string(),
+        Token::LParen => "(".to_string(),
+        Token::Number(text) | Token::Ident(text) | Token::Column(text) => format!("`{}`", text),
+        Token::Quoted(text) => format!("'{}'", text),
+    }
+}
+
+/// Apply a function by name. The first argument is the value it works on.
+fn call(name: &str, mut args: Vec<Expr>) -> Result<Expr, String> {
+    let name = name.to_lowercase();
+    let arity = |min: usize, max: usize| {
+        if (min..=max).contains(&args.len()) {
+            Ok(())
+        } else if min == max {
+            Err(format!("{}() takes {} argument(s)", name, min))
+        } else {
+            Err(format!("{}() takes {} to {} arguments", name, min, max))
+        }
+    };
+    match name.as_str() {
+        "concat" | "coalesce" => {
+            if args.is_empty() {
+                return Err(format!("{}() needs an argument", name));
+            }
+            return Ok(if name == "concat" {
+                concat_str(args, "", true)
+            } else {
+                coalesce(&args)
+            });
+        }
+        "round" => {
+            arity(1, 2)?;
+            let decimals = match args.get(1) {
+                None => 0,
+                Some(Expr::Literal(LiteralValue::Int(n))) if *n >= 0 => *n as u32,
+                Some(_) => return Err("round() takes a whole number of decimals".to_string()),
+            };
+            return Ok(args.remove(0).round(decimals));
+        }
+        _ => arity(1, 1)?,
+    }
+    let x = args.remove(0);
+    Ok(match name.as_str() {
+        "upper" => x.str().to_uppercase(),
+        "lower" => x.str().to_lowercase(),
+        "trim" => x.str().strip_chars(lit(NULL)),
+        "len" => x.str().len_chars(),
+        "abs" => x.abs(),
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        "sqrt" => x.sqrt(),
+        "ln" => x.log(std::f64::consts::E),
+        "log" => x.log(10.0),
+        "exp" => x.exp(),
+        "year" => x.dt().year(),
+        "month" => x.dt().month(),
+        "day" => x.dt().day(),
+        "hour" => x.dt().hour(),
+        "minute" => x.dt().minute(),
+        "second" => x.dt().second(),
+        "weekday" => x.dt().weekday(),
+        "date" => x.dt().date(),
+        "int" => x.strict_cast(DataType::Int64),
+        "float" => x.strict_cast(DataType::Float64),
+        "str" => x.cast(DataType::String),
+        _ => return Err(format!("unknown function {}()", name)),
+    })
+}
+
+/// Parse `name = expression` against `schema`. Without `name =`, the expression
+/// text itself names the column.
+pub fn parse_computed_column(input: &str, schema: &Schema) -> Result<(String, Expr), String> {
+    let mut tokens = tokenize(input)?;
+    let name = match tokens.get(..2) {
+        Some([Token::Ident(name) | Token::Column(name), Token::Assign]) => {
+            let name = name.clone();
+            tokens.drain(..2);
+            name
+        }
+        _ => input.trim().to_string(),
+    };
+    if tokens.is_empty() {
+        return Err("expected an expression".to_string());
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        schema,
+    };
+    let expr = parser.comparison()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {}", describe(token)));
+    }
+    Ok((name.clone(), expr.alias(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn df() -> DataFrame {
+        df! {
+            "name" => ["alice", " Bob "],
+            "revenue" => [200i64, 50],
+            "cost" => [150i64, 60],
+            "when" => ["2026-03-01", "2025-12-24"],
+        }
+        .unwrap()
+        .lazy()
+        .with_column(col("when").str().to_date(StrptimeOptions::default()))
+        .collect()
+        .unwrap()
+    }
+
+    fn eval(input: &str) -> Result<(String, Column), String> {
+        let df = df();
+        let (name, expr) = parse_computed_column(input, df.schema())?;
+        let out = df
+            .lazy()
+            .select([expr])
+            .collect()
+            .map_err(|e| e.to_string())?;
+        Ok((
+            name,
+            out.column(&out.get_column_names()[0].clone())
+                .unwrap()
+                .clone(),
+        ))
+    }
+
+    fn values(input: &str) -> Vec<String> {
+        let (_, column) = eval(input).unwrap();
+        (0..column.len())
+            .map(|i| column.get(i).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (name, _) = eval("margin = (revenue - cost) / revenue").unwrap();
+        assert_eq!(name, "margin");
+        assert_eq!(
+            values("margin = (revenue - cost) / revenue"),
+            vec!["0.25", "-0.2"]
+        );
+        assert_eq!(values("revenue + cost * 2"), vec!["500", "170"]);
+        assert_eq!(values("revenue % 7"), vec!["4", "1"]);
+        assert_eq!(values("-cost + revenue"), vec!["50", "-10"]);
+    }
+
+    #[test]
+    fn test_functions_and_methods() {
+        assert_eq!(values("upper(name)"), vec!["\"ALICE\"", "\" BOB \""]);
+        assert_eq!(values("name.trim().len()"), vec!["5", "3"]);
+        assert_eq!(values("year = when.year()"), vec!["2026", "2025"]);
+        assert_eq!(values("round(cost / 7, 1)"), vec!["21.4", "8.6"]);
+        assert_eq!(
+            values("concat(name.trim(), '-', str(cost))"),
+            vec!["\"alice-150\"", "\"Bob-60\""]
+        );
+        assert_eq!(
+            values("concat(name.trim(), '''s')"),
+            vec!["\"alice's\"", "\"Bob's\""]
+        );
+        assert_eq!(values("profitable = revenue > cost"), vec!["true", "false"]);
+        assert_eq!(values("round(log(revenue * 5), 2)"), vec!["3.0", "2.4"]);
+        assert_eq!(values("round(ln(revenue), 2)"), vec!["5.3", "3.91"]);
+    }
+
+    #[test]
+    fn test_name_defaults_to_expression() {
+        let (name, _) = eval("upper(name)").unwrap();
+        assert_eq!(name, "upper(name)");
+        let (name, _) = eval("`net rev` = revenue - cost").unwrap();
+        assert_eq!(name, "net rev");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            eval("x = price * 2").unwrap_err(),
+            "no column named `price`"
+        );
+        assert_eq!(
+            eval("x = shout(name)").unwrap_err(),
+            "unknown function shout()"
+        );
+        assert_eq!(eval("x = (cost").unwrap_err(), "expected closing )");
+        assert_eq!(eval("x = cost cost").unwrap_err(), "unexpected `cost`");
+        assert_eq!(eval("x =").unwrap_err(), "expected an expression");
+        assert_eq!(
+            eval("x = upper()").unwrap_err(),
+            "upper() takes 1 argument(s)"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use crate::app::App;
+use crate::cli::DiffOptions;
+use crate::loader::load_dataframe;
+use polars::prelude::*;
+
+/// Leading column of a diff view saying what happened to each row.
+pub const DIFF_STATUS_COLUMN: &str = "_diff";
+pub const ADDED: &str = "added";
+pub const REMOVED: &str = "removed";
+pub const CHANGED: &str = "changed";
+/// Trailing column listing, for each changed row, the columns that changed.
+pub const DIFF_CHANGED_COLUMN: &str = "_changed";
+/// Separates the old and new value of a changed cell.
+pub const CHANGE_ARROW: &str = " → ";
+
+const IN_OLD: &str = "__in_old";
+const IN_NEW: &str = "__in_new";
+
+/// What differs between two files, besides the rows themselves.
+#[derive(Debug, Default, PartialEq)]
+pub struct DiffSummary {
+    pub added_rows: usize,
+    pub removed_rows: usize,
+    pub changed_rows: usize,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub dtype_changes: Vec<(String, DataType, DataType)>,
+    pub column_changes: Vec<(String, usize)>, // changed cells per shared column
+}
+
+fn old_name(name: &str) -> String {
+    format!("__old_{}", name)
+}
+
+fn new_name(name: &str) -> String {
+    format!("__new_{}", name)
+}
+
+/// Match the rows of `old` and `new` on `keys` and return the rows that were
+/// added, removed or changed. Values are shown as text; a changed cell reads
+/// `old → new` and its column is listed in `_changed`. Columns only one file
+/// has are shown but not compared. `names` label the two files in errors.
+pub fn diff_frames(
+    old: &DataFrame,
+    new: &DataFrame,
+    keys: &[String],
+    names: [&str; 2],
+) -> Result<(DataFrame, DiffSummary), Box<dyn std::error::Error>> {
+    for key in keys {
+        if !old.schema().contains(key) || !new.schema().contains(key) {
+            return Err(format!("key column `{}` must exist in both files", key).into());
+        }
+    }
+    // Rows are matched one to one, so a repeated key would multiply them.
+    for (df, name) in [old, new].into_iter().zip(names) {
+        if df.select(keys.iter().cloned())?.is_duplicated()?.any() {
+            return Err(format!("key `{}` is not unique in {}", keys.join(", "), name).into());
+        }
+    }
+    let old_schema = old.schema();
+    let new_schema = new.schema();
+    let is_key = |name: &str| keys.iter().any(|k| k == name);
+    let shared: Vec<String> = old_schema
+        .iter_names()
+        .filter(|n| new_schema.contains(n) && !is_key(n))
+        .map(|n| n.to_string())
+        .collect();
+    let mut summary = DiffSummary {
+        added_columns: new_schema
+            .iter_names()
+            .filter(|n| !old_schema.contains(n))
+            .map(|n| n.to_string())
+            .collect(),
+        removed_columns: old_schema
+            .iter_names()
+            .filter(|n| !new_schema.contains(n))
+            .map(|n| n.to_string())
+            .collect(),
+        dtype_changes: old_schema
+            .iter()
+            .filter_map(|(name, old_dtype)| {
+                let new_dtype = new_schema.get(name)?;
+                (new_dtype != old_dtype)
+                    .then(|| (name.to_string(), old_dtype.clone(), new_dtype.clone()))
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    // Every value is compared as text, so a column whose type changed still
+    // matches where its values read the same. Numbers that changed type are
+    // read as floats first, so `30` and `30.0` match.
+    let widened: Vec<&str> = summary
+        .dtype_changes
+        .iter()
+        .filter(|(_, o, n)| o.is_primitive_numeric() && n.is_primitive_numeric())
+        .map(|(name, _, _)| name.as_str())
+        .collect();
+    let side = |df: &DataFrame, rename: fn(&str) -> String, flag: &str| {
+        let mut exprs: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+        exprs.extend(
+            df.get_column_names()
+                .iter()
+                .filter(|n| !is_key(n))
+                .map(|n| {
+                    let value = col(n.as_str());
+                    let value = if widened.contains(&n.as_str()) {
+                        value.cast(DataType::Float64)
+                    } else {
+                        value
+                    };
+                    value.cast(DataType::String).alias(rename(n.as_str()))
+                }),
+        );
+        exprs.push(lit(true).alias(flag));
+        df.clone().lazy().select(exprs)
+    };
+    let keys_expr: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+    let joined = side(old, old_name, IN_OLD)
+        .join(
+            side(new, new_name, IN_NEW),
+            keys_expr.clone(),
+            keys_expr,
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .collect()?;
+
+    let changed = |name: &str| col(old_name(name)).neq_missing(col(new_name(name)));
+    let any_changed = shared
+        .iter()
+        .fold(lit(false), |acc, name| acc.or(changed(name)));
+    let status = when(col(IN_OLD).is_null())
+        .then(lit(ADDED))
+        .when(col(IN_NEW).is_null())
+        .then(lit(REMOVED))
+        .when(any_changed)
+        .then(lit(CHANGED))
+        .otherwise(lit(NULL).cast(DataType::String))
+        .alias(DIFF_STATUS_COLUMN);
+    let with_status = joined.lazy().with_column(status).collect()?;
+
+    let counts = with_status
+        .clone()
+        .lazy()
+        .select(
+            [ADDED, REMOVED, CHANGED]
+                .iter()
+                .map(|s| col(DIFF_STATUS_COLUMN).eq(lit(*s)).sum().alias(*s))
+                .chain(shared.iter().map(|name| {
+                    changed(name)
+                        .and(col(DIFF_STATUS_COLUMN).eq(lit(CHANGED)))
+                        .sum()
+                        .alias(name.as_str())
+                }))
+                .collect::<Vec<_>>(),
+        )
+        .collect()?;
+    let count = |name: &str| -> PolarsResult<usize> {
+        let value = counts.column(name)?.cast(&DataType::UInt64)?;
+        Ok(value.u64()?.get(0).unwrap_or(0) as usize)
+    };
+    summary.added_rows = count(ADDED)?;
+    summary.removed_rows = count(REMOVED)?;
+    summary.changed_rows = count(CHANGED)?;
+    for name in &shared {
+        let n = count(name)?;
+        if n > 0 {
+            summary.column_changes.push((name.clone(), n));
+        }
+    }
+
+    // Columns in the order of the new file, then those it dropped.
+    let mut exprs = vec![col(DIFF_STATUS_COLUMN)];
+    exprs.extend(keys.iter().map(|k| col(k.as_str())));
+    for name in new.get_column_names().iter().filter(|n| !is_key(n)) {
+        let name = name.as_str();
+        exprs.push(if shared.iter().any(|s| s == name) {
+            let (old_value, new_value) = (col(old_name(name)), col(new_name(name)));
+            let status = col(DIFF_STATUS_COLUMN);
+            when(status.clone().eq(lit(REMOVED)))
+                .then(old_value.clone())
+                .when(status.eq(lit(CHANGED)).and(changed(name)))
+                .then(concat_str(
+                    [
+                        old_value.fill_null(lit("null")),
+                        new_value.clone().fill_null(lit("null")),
+                    ],
+                    CHANGE_ARROW,
+                    false,
+                ))
+                .otherwise(new_value)
+                .alias(name)
+        } else {
+            col(new_name(name)).alias(name)
+        });
+    }
+    exprs.extend(
+        summary
+            .removed_columns
+            .iter()
+            .map(|name| col(old_name(name)).alias(name.as_str())),
+    );
+    if !shared.is_empty() {
+        let flags = shared.iter().map(|name| {
+            when(col(DIFF_STATUS_COLUMN).eq(lit(CHANGED)).and(changed(name)))
+                .then(lit(name.as_str()))
+                .otherwise(lit(NULL).cast(DataType::String))
+        });
+        exprs.push(
+            concat_list(flags.collect::<Vec<_>>())?
+                .list()
+                .drop_nulls()
+                .alias(DIFF_CHANGED_COLUMN),
+        );
+    }
+    let df = with_status
+        .lazy()
+        .filter(col(DIFF_STATUS_COLUMN).is_not_null())
+        .select(exprs)
+        .sort(keys, SortMultipleOptions::default())
+        .collect()?;
+    Ok((df, summary))
+}
+
+/// Load both files of `iron-sight diff` and open their differences.
+pub fn open_diff(opts: &DiffOptions) -> Result<App, Box<dyn std::error::Error>> {
+    let old = load_dataframe(&opts.old, &opts.csv)?;
+    let new = load_dataframe(&opts.new, &opts.csv)?;
+    let (df, summary) = diff_frames(&old, &new, &opts.key, [&opts.old, &opts.new])?;
+    let mut app = App::new(df, format!("{} → {}", opts.old, opts.new));
+    app.diff = Some(summary);
+    // The changed-column lists only drive the highlighting.
+    app.column_layout
+        .hidden
+        .push(DIFF_CHANGED_COLUMN.to_string());
+    app.show_diff_summary = true;
+    app.csv_options = opts.csv.clone();
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 2] = ["old.csv", "new.csv"];
+
+    fn old() -> DataFrame {
+        df! {
+            "id" => [1i64, 2, 3, 4],
+            "name" => ["Alice", "Bob", "Charlie", "Dana"],
+            "age" => [30i64, 25, 35, 41],
+            "team" => ["a", "b", "c", "d"],
+        }
+        .unwrap()
+    }
+
+    fn new() -> DataFrame {
+        df! {
+            "id" => [1i64, 2, 4, 5],
+            "name" => ["Alice", "Robert", "Dana", "Eve"],
+            "age" => [30.0f64, 26.0, 41.5, 22.0],
+            "city" => ["Oslo", "Rome", "Lima", "Kyiv"],
+        }
+        .unwrap()
+    }
+
+    fn strings(df: &DataFrame, name: &str) -> Vec<Option<String>> {
+        df.column(name)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.map(str::to_string))
+            .collect()
+    }
+
+    fn changed_columns(df: &DataFrame) -> Vec<Vec<String>> {
+        let lists = df.column(DIFF_CHANGED_COLUMN).unwrap().list().unwrap();
+        lists
+            .into_iter()
+            .map(|names| {
+                let names = names.unwrap();
+                let names = names.str().unwrap();
+                names.into_no_null_iter().map(str::to_string).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rows_and_cells() {
+        let (df, _) = diff_frames(&old(), &new(), &["id".to_string()], NAMES).unwrap();
+        assert_eq!(
+            df.get_column_names(),
+            vec!["_diff", "id", "name", "age", "city", "team", "_changed"]
+        );
+        let status = strings(&df, DIFF_STATUS_COLUMN);
+        let s = |v: &str| Some(v.to_string());
+        assert_eq!(status, vec![s(CHANGED), s(REMOVED), s(CHANGED), s(ADDED)]);
+        assert_eq!(
+            strings(&df, "name"),
+            vec![s("Bob → Robert"), s("Charlie"), s("Dana"), s("Eve")]
+        );
+        assert_eq!(
+            strings(&df, "age"),
+            vec![s("25.0 → 26.0"), s("35.0"), s("41.0 → 41.5"), s("22.0")]
+        );
+        assert_eq!(
+            changed_columns(&df),
+            vec![vec!["name", "age"], vec![], vec!["age"], vec![]]
+        );
+    }
+
+    #[test]
+    fn test_arrow_in_a_value_is_not_a_change() {
+        let old = df! { "id" => [1i64], "note" => ["a → b"], "n" => [1i64] }.unwrap();
+        let new = df! { "id" => [1i64], "note" => ["a → b"], "n" => [2i64] }.unwrap();
+        let (df, _) = diff_frames(&old, &new, &["id".to_string()], NAMES).unwrap();
+        assert_eq!(changed_columns(&df), vec![vec!["n"]]);
+    }
+
+    #[test]
+    fn test_export_leaves_out_changed_columns() {
+        let (df, summary) = diff_frames(&old(), &new(), &["id".to_string()], NAMES).unwrap();
+        let mut app = App::new(df, "old.csv → new.csv".to_string());
+        app.diff = Some(summary);
+        let path = std::env::temp_dir().join("iron_sight_export_diff.csv");
+        app.export_input = path.to_string_lossy().into_owned();
+        app.export_view();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("_diff,id,name,age,city,team\n"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let (_, summary) = diff_frames(&old(), &new(), &["id".to_string()], NAMES).unwrap();
+        assert_eq!(
+            (
+                summary.added_rows,
+                summary.removed_rows,
+                summary.changed_rows
+            ),
+            (1, 1, 2)
+        );
+        assert_eq!(summary.added_columns, vec!["city"]);
+        assert_eq!(summary.removed_columns, vec!["team"]);
+        assert_eq!(
+            summary.dtype_changes,
+            vec![("age".to_string(), DataType::Int64, DataType::Float64)]
+        );
+        assert_eq!(
+            summary.column_changes,
+            vec![("name".to_string(), 1), ("age".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_identical_frames() {
+        let (df, summary) = diff_frames(&old(), &old(), &["id".to_string()], NAMES).unwrap();
+        assert_eq!(df.height(), 0);
+        assert_eq!(summary, DiffSummary::default());
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let err = diff_frames(&old(), &new(), &["team".to_string()], NAMES).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "key column `team` must exist in both files"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let dup = df! {
+            "id" => [1i64, 1, 2],
+            "name" => ["a", "b", "c"],
+        }
+        .unwrap();
+        let keys = ["id".to_string()];
+        let err = diff_frames(&dup, &dup, &keys, NAMES).unwrap_err();
+        assert_eq!(err.to_string(), "key `id` is not unique in old.csv");
+        let unique = dup.slice(1, 2);
+        let err = diff_frames(&unique, &dup, &keys, NAMES).unwrap_err();
+        assert_eq!(err.to_string(), "key `id` is not unique in new.csv");
+        // Together the columns identify each row.
+        let keys = ["id".to_string(), "name".to_string()];
+        assert!(diff_frames(&dup, &dup, &keys, NAMES).is_ok());
+    }
+}
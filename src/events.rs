@@ -1,4 +1,4 @@
-use crate::app::{App, Mode, PlotType};
+use crate::app::{App, Filter, Mode, PlotType, StatusMessage};
 use crate::ui::ui;
 use crossterm::event;
 
@@ -12,157 +12,372 @@ pub fn run_app(
         terminal.draw(|frame| ui(frame, &mut app))?;
 
         if let event::Event::Key(key) = event::read()? {
-            match app.mode {
-                Mode::Normal => match key.code {
-                    event::KeyCode::Char('q') => app.should_quit = true,
-                    event::KeyCode::Down => app.state.select_next(),
-                    event::KeyCode::Up => app.state.select_previous(),
-                    event::KeyCode::Left => app.state.select_previous_column(),
-                    event::KeyCode::Right => app.state.select_next_column(),
-                    event::KeyCode::Char('j') => app.state.select_next(),
-                    event::KeyCode::Char('k') => app.state.select_previous(),
-                    event::KeyCode::Char('h') => app.state.select_previous_column(),
-                    event::KeyCode::Char('l') => app.state.select_next_column(),
-                    event::KeyCode::Char('g') => app.state.select_first(),
-                    event::KeyCode::Char('G') => app.state.select_last(),
-                    event::KeyCode::PageDown => app.state.scroll_down_by(PAGE_SCROLL_AMOUNT),
-                    event::KeyCode::PageUp => app.state.scroll_up_by(PAGE_SCROLL_AMOUNT),
-                    event::KeyCode::Home => app.state.select_first(),
-                    event::KeyCode::End => app.state.select_last(),
-                    event::KeyCode::Char('_') => autofit_column(&mut app),
-                    event::KeyCode::Char('/') => enter_search_mode(&mut app),
-                    event::KeyCode::Char('n') => go_to_next_search_result(&mut app),
-                    event::KeyCode::Char('N') => go_to_previous_search_result(&mut app),
-                    event::KeyCode::Char('f') => enter_filter_mode(&mut app),
-                    event::KeyCode::Char('F') => clear_filters(&mut app),
-                    event::KeyCode::Char('s') => app.sort_by_column(),
-                    event::KeyCode::Char('S') => app.show_stats = !app.show_stats,
-                    event::KeyCode::Char('b') => app.toggle_groupby_key(),
-                    event::KeyCode::Char('a') => app.cycle_groupby_agg(),
-                    event::KeyCode::Char('B') => {
-                        if app.groupby_active {
-                            app.clear_groupby();
-                        } else {
-                            app.apply_groupby();
-                        }
-                    }
-                    event::KeyCode::Char('?') => app.show_help = !app.show_help,
-                    event::KeyCode::Esc => app.show_help = false,
-                    event::KeyCode::Char('=') => app.autofit_all_columns(),
-                    event::KeyCode::Char('p') if !app.df.is_empty() => {
-                        app.plot_y_col = app.state.selected_column();
-                        app.mode = Mode::PlotPickX;
-                    }
-                    event::KeyCode::Char('i') if !app.df.is_empty() => {
-                        app.build_columns_profile();
-                        app.mode = Mode::ColumnsView;
-                    }
-                    event::KeyCode::Char('u') if !app.df.is_empty() => {
-                        app.build_unique_values();
-                        app.mode = Mode::UniqueValues;
-                    }
-                    _ => {}
-                },
-                Mode::Search => match key.code {
-                    event::KeyCode::Backspace => pop_char_from_search_query(&mut app),
-                    event::KeyCode::Enter => to_first_search_query_result(&mut app),
-                    event::KeyCode::Char(c) => push_char_to_search_query(&mut app, c),
-                    event::KeyCode::Esc => from_search_to_normal_mode(&mut app),
-                    _ => {}
-                },
-                Mode::PlotPickX => match key.code {
-                    event::KeyCode::Left | event::KeyCode::Char('h') => {
-                        app.state.select_previous_column()
-                    }
-                    event::KeyCode::Right | event::KeyCode::Char('l') => {
-                        app.state.select_next_column()
-                    }
-                    event::KeyCode::Enter => {
-                        app.plot_x_col = app.state.selected_column();
-                        app.mode = Mode::Plot;
-                    }
-                    event::KeyCode::Esc => {
-                        app.plot_y_col = None;
-                        app.mode = Mode::Normal;
-                    }
-                    _ => {}
-                },
-                Mode::Plot => match key.code {
-                    event::KeyCode::Char('t') => {
-                        app.plot_type = match app.plot_type {
-                            PlotType::Line => PlotType::Bar,
-                            PlotType::Bar => PlotType::Histogram,
-                            PlotType::Histogram => PlotType::Line,
-                        };
-                    }
-                    event::KeyCode::Esc | event::KeyCode::Char('p') => app.mode = Mode::Normal,
-                    event::KeyCode::Char('q') => app.should_quit = true,
-                    _ => {}
-                },
-                Mode::UniqueValues => match key.code {
-                    event::KeyCode::Esc => app.mode = Mode::Normal,
-                    event::KeyCode::Down | event::KeyCode::Char('j') => {
-                        app.unique_values_state.select_next()
-                    }
-                    event::KeyCode::Up | event::KeyCode::Char('k') => {
-                        app.unique_values_state.select_previous()
-                    }
-                    event::KeyCode::Backspace => {
-                        app.unique_values_query.pop();
-                        app.filter_unique_values();
-                    }
-                    event::KeyCode::Enter => {
-                        if let Some(idx) = app.unique_values_state.selected() {
-                            if let Some((value, _)) = app.unique_values_filtered.get(idx) {
-                                let filter = format!("= {}", value);
-                                let col = app.unique_values_col;
-                                app.filters.push((col, filter));
-                                app.update_filter();
-                            }
-                        }
-                        app.mode = Mode::Normal;
-                    }
-                    event::KeyCode::Char(c) => {
-                        app.unique_values_query.push(c);
-                        app.filter_unique_values();
-                    }
-                    _ => {}
-                },
-                Mode::ColumnsView => match key.code {
-                    event::KeyCode::Down | event::KeyCode::Char('j') => {
-                        app.columns_view_state.select_next()
-                    }
-                    event::KeyCode::Up | event::KeyCode::Char('k') => {
-                        app.columns_view_state.select_previous()
-                    }
-                    event::KeyCode::Char('g') | event::KeyCode::Home => {
-                        app.columns_view_state.select_first()
-                    }
-                    event::KeyCode::Char('G') | event::KeyCode::End => {
-                        app.columns_view_state.select_last()
-                    }
-                    event::KeyCode::Enter => {
-                        let col = app.columns_view_state.selected().unwrap_or(0);
-                        app.state.select_column(Some(col));
-                        app.mode = Mode::Normal;
-                    }
-                    event::KeyCode::Esc | event::KeyCode::Char('i') => {
-                        app.mode = Mode::Normal;
+            handle_key(&mut app, key);
+        }
+    }
+    Ok(())
+}
+
+/// Apply one key press, recording any view change for undo.
+fn handle_key(app: &mut App, key: event::KeyEvent) {
+    if matches!(app.mode, Mode::Normal) {
+        app.status_message = None;
+        if undo_or_redo(app, key) {
+            return;
+        }
+    }
+    if matches!(app.mode, Mode::ColumnChooser) {
+        app.status_message = None;
+    }
+    let before = app.snapshot();
+    match app.mode {
+        Mode::Normal => match key.code {
+            event::KeyCode::Char('q') => app.should_quit = true,
+            event::KeyCode::Down => app.state.select_next(),
+            event::KeyCode::Up => app.state.select_previous(),
+            event::KeyCode::Left => app.select_previous_column(),
+            event::KeyCode::Right => app.select_next_column(),
+            event::KeyCode::Char('j') => app.state.select_next(),
+            event::KeyCode::Char('k') => app.state.select_previous(),
+            event::KeyCode::Char('h') => app.select_previous_column(),
+            event::KeyCode::Char('l') => app.select_next_column(),
+            event::KeyCode::Char('g') => app.state.select_first(),
+            event::KeyCode::Char('G') => app.state.select_last(),
+            event::KeyCode::PageDown => app.state.scroll_down_by(PAGE_SCROLL_AMOUNT),
+            event::KeyCode::PageUp => app.state.scroll_up_by(PAGE_SCROLL_AMOUNT),
+            event::KeyCode::Home => app.state.select_first(),
+            event::KeyCode::End => app.state.select_last(),
+            event::KeyCode::Char('_') => autofit_column(app),
+            event::KeyCode::Char('/') => enter_search_mode(app),
+            event::KeyCode::Char('n') => go_to_next_search_result(app),
+            event::KeyCode::Char('N') => go_to_previous_search_result(app),
+            event::KeyCode::Char('f') => enter_filter_mode(app),
+            event::KeyCode::Char('F') => clear_filters(app),
+            event::KeyCode::Char('L') if !pivot_blocks_filters(app) => {
+                app.build_filter_impacts();
+                app.filter_list_state.select(if app.filters.is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+                app.mode = Mode::FilterList;
+            }
+            event::KeyCode::Char('s') => app.sort_by_column(),
+            event::KeyCode::Char('+') => app.add_sort_column(),
+            event::KeyCode::Char('-') => app.remove_sort_column(),
+            event::KeyCode::Char('z') => app.toggle_sort_nulls(),
+            event::KeyCode::Char('S') => app.show_stats = !app.show_stats,
+            event::KeyCode::Char('b') => app.toggle_groupby_key(),
+            event::KeyCode::Char('a') => app.cycle_groupby_agg(),
+            event::KeyCode::Char('A') => app.add_groupby_agg(),
+            event::KeyCode::Enter => app.drill_down(),
+            event::KeyCode::Backspace => {
+                app.drill_up();
+            }
+            event::KeyCode::Char('B') => {
+                if app.groupby_active {
+                    app.clear_groupby();
+                } else {
+                    app.apply_groupby();
+                }
+            }
+            event::KeyCode::Char('P') => {
+                if app.pivot.is_some() {
+                    app.clear_pivot();
+                } else if app.groupby_active {
+                    app.status_message = Some(StatusMessage::Error(
+                        "Clear the group-by (B) before pivoting".to_string(),
+                    ));
+                } else if app.has_data() {
+                    app.open_pivot_setup();
+                }
+            }
+            event::KeyCode::Char('?') => app.show_help = !app.show_help,
+            event::KeyCode::Esc => {
+                app.show_help = false;
+                app.show_diff_summary = false;
+            }
+            event::KeyCode::Char('D') if app.diff.is_some() => {
+                app.show_diff_summary = !app.show_diff_summary
+            }
+            event::KeyCode::Char('=') => app.autofit_all_columns(),
+            event::KeyCode::Char('p') if app.has_data() => {
+                app.plot_y_col = app.state.selected_column();
+                app.mode = Mode::PlotPickX;
+            }
+            event::KeyCode::Char('i') if app.has_data() => {
+                app.build_columns_profile();
+                app.mode = Mode::ColumnsView;
+            }
+            event::KeyCode::Char('C') if app.has_data() => app.open_column_chooser(),
+            event::KeyCode::Char('Z') => app.toggle_freeze(),
+            event::KeyCode::Char('u') if app.has_data() => {
+                app.build_unique_values();
+                app.mode = Mode::UniqueValues;
+            }
+            event::KeyCode::Char('w') => enter_export_mode(app),
+            event::KeyCode::Char('Q') => app.mode = Mode::Sql,
+            event::KeyCode::Char('c') if app.has_data() => {
+                app.compute_input = String::new();
+                app.compute_error = None;
+                app.mode = Mode::Compute;
+            }
+            event::KeyCode::Char('J') => {
+                app.join_input = String::new();
+                app.mode = Mode::Join;
+            }
+            _ => {}
+        },
+        Mode::Sql => match key.code {
+            event::KeyCode::Backspace => {
+                app.sql_input.pop();
+            }
+            event::KeyCode::Enter => run_sql(app),
+            event::KeyCode::Char(c) => app.sql_input.push(c),
+            event::KeyCode::Esc => app.mode = Mode::Normal,
+            _ => {}
+        },
+        Mode::Compute => match key.code {
+            event::KeyCode::Backspace => {
+                app.compute_input.pop();
+                app.compute_error = None;
+            }
+            event::KeyCode::Enter => confirm_compute(app),
+            event::KeyCode::Char(c) => {
+                app.compute_input.push(c);
+                app.compute_error = None;
+            }
+            event::KeyCode::Esc => app.mode = Mode::Normal,
+            _ => {}
+        },
+        Mode::Join => match key.code {
+            event::KeyCode::Backspace => {
+                app.join_input.pop();
+            }
+            event::KeyCode::Enter => start_join(app),
+            event::KeyCode::Char(c) => app.join_input.push(c),
+            event::KeyCode::Esc => app.mode = Mode::Normal,
+            _ => {}
+        },
+        Mode::JoinSetup => match key.code {
+            event::KeyCode::Down | event::KeyCode::Char('j') => app.join_form_state.select_next(),
+            event::KeyCode::Up | event::KeyCode::Char('k') => app.join_form_state.select_previous(),
+            event::KeyCode::Right | event::KeyCode::Char('l') | event::KeyCode::Char(' ') => {
+                app.shift_join_field(true)
+            }
+            event::KeyCode::Left | event::KeyCode::Char('h') => app.shift_join_field(false),
+            event::KeyCode::Char('+') => app.add_join_key(),
+            event::KeyCode::Char('-') => app.remove_join_key(),
+            event::KeyCode::Enter => {
+                app.mode = Mode::Normal;
+                app.apply_join();
+            }
+            event::KeyCode::Esc => {
+                app.join_right = None;
+                app.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::Export => match key.code {
+            event::KeyCode::Backspace => {
+                app.export_input.pop();
+            }
+            event::KeyCode::Enter => confirm_export(app),
+            event::KeyCode::Char(c) => app.export_input.push(c),
+            event::KeyCode::Esc => app.mode = Mode::Normal,
+            _ => {}
+        },
+        Mode::ConfirmOverwrite => match key.code {
+            event::KeyCode::Char('y') | event::KeyCode::Char('Y') => {
+                app.export_view();
+                app.mode = Mode::Normal;
+            }
+            _ => app.mode = Mode::Export,
+        },
+        Mode::Search => match key.code {
+            event::KeyCode::Backspace => pop_char_from_search_query(app),
+            event::KeyCode::Enter => to_first_search_query_result(app),
+            event::KeyCode::Char(c) => push_char_to_search_query(app, c),
+            event::KeyCode::Esc => from_search_to_normal_mode(app),
+            _ => {}
+        },
+        Mode::PlotPickX => match key.code {
+            event::KeyCode::Left | event::KeyCode::Char('h') => app.select_previous_column(),
+            event::KeyCode::Right | event::KeyCode::Char('l') => app.select_next_column(),
+            event::KeyCode::Enter => {
+                app.plot_x_col = app.state.selected_column();
+                app.mode = Mode::Plot;
+            }
+            event::KeyCode::Esc => {
+                app.plot_y_col = None;
+                app.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::Plot => match key.code {
+            event::KeyCode::Char('t') => {
+                app.plot_type = match app.plot_type {
+                    PlotType::Line => PlotType::Bar,
+                    PlotType::Bar => PlotType::Histogram,
+                    PlotType::Histogram => PlotType::Line,
+                };
+            }
+            event::KeyCode::Esc | event::KeyCode::Char('p') => app.mode = Mode::Normal,
+            event::KeyCode::Char('q') => app.should_quit = true,
+            _ => {}
+        },
+        Mode::UniqueValues => match key.code {
+            event::KeyCode::Esc => app.mode = Mode::Normal,
+            event::KeyCode::Down | event::KeyCode::Char('j') => {
+                app.unique_values_state.select_next()
+            }
+            event::KeyCode::Up | event::KeyCode::Char('k') => {
+                app.unique_values_state.select_previous()
+            }
+            event::KeyCode::Backspace => {
+                app.unique_values_query.pop();
+                app.filter_unique_values();
+            }
+            event::KeyCode::Enter => {
+                if let Some(idx) = app
+                    .unique_values_state
+                    .selected()
+                    .filter(|_| !pivot_blocks_filters(app))
+                {
+                    if let Some((value, _)) = app.unique_values_filtered.get(idx) {
+                        let filter = format!("= \"{}\"", value.replace('"', "\"\""));
+                        let col = app.unique_values_col;
+                        app.filters.push(Filter::new(col, filter));
+                        app.update_filter();
                     }
-                    event::KeyCode::Char('q') => app.should_quit = true,
-                    _ => {}
-                },
-                Mode::Filter => match key.code {
-                    event::KeyCode::Backspace => pop_char_from_filter_query(&mut app),
-                    event::KeyCode::Enter => to_normal_mode_with_filter(&mut app),
-                    event::KeyCode::Char(c) => push_char_to_filter_query(&mut app, c),
-                    event::KeyCode::Esc => from_filter_to_normal_mode(&mut app),
-                    _ => {}
-                },
+                }
+                app.mode = Mode::Normal;
+            }
+            event::KeyCode::Char(c) => {
+                app.unique_values_query.push(c);
+                app.filter_unique_values();
+            }
+            _ => {}
+        },
+        Mode::ColumnsView => match key.code {
+            event::KeyCode::Down | event::KeyCode::Char('j') => {
+                app.columns_view_state.select_next()
+            }
+            event::KeyCode::Up | event::KeyCode::Char('k') => {
+                app.columns_view_state.select_previous()
+            }
+            event::KeyCode::Char('g') | event::KeyCode::Home => {
+                app.columns_view_state.select_first()
+            }
+            event::KeyCode::Char('G') | event::KeyCode::End => app.columns_view_state.select_last(),
+            event::KeyCode::Enter => {
+                let col = app.columns_view_state.selected().unwrap_or(0);
+                app.state.select_column(Some(col));
+                app.mode = Mode::Normal;
             }
+            event::KeyCode::Esc | event::KeyCode::Char('i') => {
+                app.mode = Mode::Normal;
+            }
+            event::KeyCode::Char('q') => app.should_quit = true,
+            _ => {}
+        },
+        Mode::ColumnChooser => match key.code {
+            event::KeyCode::Down | event::KeyCode::Char('j') => {
+                app.column_chooser_state.select_next()
+            }
+            event::KeyCode::Up | event::KeyCode::Char('k') => {
+                app.column_chooser_state.select_previous()
+            }
+            event::KeyCode::Char(' ') => app.toggle_column_hidden(),
+            event::KeyCode::Char('p') => app.toggle_column_pinned(),
+            event::KeyCode::Left | event::KeyCode::Char('h') => app.move_column(false),
+            event::KeyCode::Right | event::KeyCode::Char('l') => app.move_column(true),
+            event::KeyCode::Char('r') => app.reset_column_layout(),
+            event::KeyCode::Enter => {
+                if let Some(col) = app.chooser_column().filter(|&i| !app.is_column_hidden(i)) {
+                    app.state.select_column(Some(col));
+                }
+                app.mode = Mode::Normal;
+            }
+            event::KeyCode::Esc | event::KeyCode::Char('C') => app.mode = Mode::Normal,
+            event::KeyCode::Char('q') => app.should_quit = true,
+            _ => {}
+        },
+        Mode::FilterList if app.filter_edit.is_some() => match key.code {
+            event::KeyCode::Backspace => {
+                if let Some(q) = app.filter_edit.as_mut() {
+                    q.pop();
+                }
+            }
+            event::KeyCode::Char(c) => {
+                if let Some(q) = app.filter_edit.as_mut() {
+                    q.push(c);
+                }
+            }
+            event::KeyCode::Enter => commit_filter_edit(app),
+            event::KeyCode::Esc => {
+                app.filter_edit = None;
+                app.filter_error = None;
+            }
+            _ => {}
+        },
+        Mode::FilterList => match key.code {
+            event::KeyCode::Down | event::KeyCode::Char('j') => app.filter_list_state.select_next(),
+            event::KeyCode::Up | event::KeyCode::Char('k') => {
+                app.filter_list_state.select_previous()
+            }
+            event::KeyCode::Char(' ') => change_selected_filter(app, |f| f.enabled = !f.enabled),
+            event::KeyCode::Char('!') => change_selected_filter(app, |f| f.inverted = !f.inverted),
+            event::KeyCode::Char('d') | event::KeyCode::Delete => remove_selected_filter(app),
+            event::KeyCode::Char('e') | event::KeyCode::Enter => {
+                if let Some(f) = selected_filter(app) {
+                    app.filter_edit = Some(f.query.clone());
+                }
+            }
+            event::KeyCode::Esc | event::KeyCode::Char('L') => app.mode = Mode::Normal,
+            event::KeyCode::Char('q') => app.should_quit = true,
+            _ => {}
+        },
+        Mode::PivotSetup => match key.code {
+            event::KeyCode::Down | event::KeyCode::Char('j') => app.pivot_form_state.select_next(),
+            event::KeyCode::Up | event::KeyCode::Char('k') => {
+                app.pivot_form_state.select_previous()
+            }
+            event::KeyCode::Right | event::KeyCode::Char('l') | event::KeyCode::Char(' ') => {
+                app.shift_pivot_field(true)
+            }
+            event::KeyCode::Left | event::KeyCode::Char('h') => app.shift_pivot_field(false),
+            event::KeyCode::Enter => {
+                app.mode = Mode::Normal;
+                app.apply_pivot();
+            }
+            event::KeyCode::Esc | event::KeyCode::Char('P') => app.mode = Mode::Normal,
+            _ => {}
+        },
+        Mode::Filter => match key.code {
+            event::KeyCode::Backspace => pop_char_from_filter_query(app),
+            event::KeyCode::Enter => to_normal_mode_with_filter(app),
+            event::KeyCode::Char(c) => push_char_to_filter_query(app, c),
+            event::KeyCode::Esc => from_filter_to_normal_mode(app),
+            _ => {}
+        },
+    }
+    app.record_history(before);
+}
+
+/// `U` undoes the last view change and `Ctrl-r` redoes it.
+fn undo_or_redo(app: &mut App, key: event::KeyEvent) -> bool {
+    let (done, name) = match key.code {
+        event::KeyCode::Char('U') => (app.undo(), "undo"),
+        event::KeyCode::Char('r') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+            (app.redo(), "redo")
         }
+        _ => return false,
+    };
+    if !done {
+        app.status_message = Some(StatusMessage::Info(format!("Nothing to {}", name)));
     }
-    Ok(())
+    true
 }
 
 fn autofit_column(app: &mut App) {
@@ -175,7 +390,21 @@ fn enter_search_mode(app: &mut App) {
     app.search_query = String::new();
 }
 
+/// Filters rebuild the rows from the data, which would leave them under the
+/// pivot's headers, so they wait until the pivot is cleared.
+fn pivot_blocks_filters(app: &mut App) -> bool {
+    if app.pivot.is_some() {
+        app.status_message = Some(StatusMessage::Error(
+            "Clear the pivot (P) before filtering".to_string(),
+        ));
+    }
+    app.pivot.is_some()
+}
+
 fn enter_filter_mode(app: &mut App) {
+    if pivot_blocks_filters(app) {
+        return;
+    }
     app.mode = Mode::Filter;
     app.filter_input = String::new();
 }
@@ -187,7 +416,7 @@ fn push_char_to_search_query(app: &mut App, c: char) {
 
 fn push_char_to_filter_query(app: &mut App, c: char) {
     app.filter_input.push(c);
-    app.update_filter();
+    app.preview_filter();
 }
 
 fn pop_char_from_search_query(app: &mut App) {
@@ -197,7 +426,7 @@ fn pop_char_from_search_query(app: &mut App) {
 
 fn pop_char_from_filter_query(app: &mut App) {
     app.filter_input.pop();
-    app.update_filter();
+    app.preview_filter();
 }
 
 fn to_first_search_query_result(app: &mut App) {
@@ -210,15 +439,20 @@ fn to_first_search_query_result(app: &mut App) {
 }
 
 fn to_normal_mode_with_filter(app: &mut App) {
+    // Keep the prompt open so the error stays visible and the filter can be fixed.
+    if app.filter_error.is_some() {
+        return;
+    }
     app.mode = Mode::Normal;
     if !app.filter_input.is_empty() {
-        app.filters.push((
+        app.filters.push(Filter::new(
             app.state.selected_column().unwrap_or(0),
             app.filter_input.clone(),
         ));
         app.filter_input = String::new();
-        app.update_filter();
     }
+    // Typing only previewed the rows; count them now.
+    app.update_filter();
 }
 
 fn from_search_to_normal_mode(app: &mut App) {
@@ -231,9 +465,107 @@ fn from_search_to_normal_mode(app: &mut App) {
 fn from_filter_to_normal_mode(app: &mut App) {
     app.mode = Mode::Normal;
     app.filter_input = String::new();
+    app.update_filter();
+}
+
+fn enter_export_mode(app: &mut App) {
+    app.mode = Mode::Export;
+    app.export_input = String::new();
+}
+
+fn confirm_export(app: &mut App) {
+    if app.export_input.trim().is_empty() {
+        app.mode = Mode::Normal;
+    } else if std::path::Path::new(app.export_input.trim()).exists() {
+        app.mode = Mode::ConfirmOverwrite;
+    } else {
+        app.export_view();
+        app.mode = Mode::Normal;
+    }
+}
+
+fn run_sql(app: &mut App) {
+    app.mode = Mode::Normal;
+    if app.sql_input.trim().is_empty() {
+        app.clear_sql();
+    } else {
+        app.apply_sql();
+    }
+}
+
+/// Add the typed column; on an error the prompt stays open to fix it.
+fn confirm_compute(app: &mut App) {
+    if app.compute_input.trim().is_empty() || app.add_computed_column() {
+        app.mode = Mode::Normal;
+    }
+}
+
+/// An empty path drops the joins; otherwise load the file and pick the keys.
+fn start_join(app: &mut App) {
+    app.mode = Mode::Normal;
+    if app.join_input.trim().is_empty() {
+        app.clear_join();
+    } else {
+        app.load_join_file();
+    }
+}
+
+fn selected_filter(app: &App) -> Option<&Filter> {
+    app.filters.get(app.filter_list_state.selected()?)
+}
+
+fn change_selected_filter(app: &mut App, change: impl FnOnce(&mut Filter)) {
+    let Some(idx) = app.filter_list_state.selected() else {
+        return;
+    };
+    if let Some(filter) = app.filters.get_mut(idx) {
+        change(filter);
+        app.update_filter();
+        app.build_filter_impacts();
+    }
+}
+
+fn remove_selected_filter(app: &mut App) {
+    let Some(idx) = app.filter_list_state.selected() else {
+        return;
+    };
+    if idx < app.filters.len() {
+        app.filters.remove(idx);
+        app.update_filter();
+        app.build_filter_impacts();
+        if app.filters.is_empty() {
+            app.filter_list_state.select(None);
+        } else if idx >= app.filters.len() {
+            app.filter_list_state.select(Some(app.filters.len() - 1));
+        }
+    }
+}
+
+/// Apply the edited query, keeping the editor open while it doesn't parse.
+fn commit_filter_edit(app: &mut App) {
+    let (Some(idx), Some(query)) = (app.filter_list_state.selected(), app.filter_edit.clone())
+    else {
+        return;
+    };
+    let Some(previous) = app.filters.get(idx).map(|f| f.query.clone()) else {
+        return;
+    };
+    app.filters[idx].query = query;
+    app.update_filter();
+    if let Some(err) = app.filter_error.take() {
+        app.filters[idx].query = previous;
+        app.update_filter();
+        app.filter_error = Some(err);
+        return;
+    }
+    app.filter_edit = None;
+    app.build_filter_impacts();
 }
 
 fn clear_filters(app: &mut App) {
+    if pivot_blocks_filters(app) {
+        return;
+    }
     app.filter_input = String::new();
     app.filters = Vec::new();
     app.update_filter();
@@ -264,3 +596,84 @@ fn go_to_previous_search_result(app: &mut App) {
     app.state
         .select(Some(app.search_results[app.search_cursor]));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    fn press(app: &mut App, code: event::KeyCode) {
+        handle_key(app, event::KeyEvent::from(code));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, event::KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_undo_typed_filter() {
+        let df = df! { "n" => [1i64, 2, 3, 4] }.unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        press(&mut app, event::KeyCode::Char('f'));
+        type_text(&mut app, "> 2");
+        press(&mut app, event::KeyCode::Enter);
+        assert_eq!(app.filters.len(), 1);
+        assert_eq!(app.view.height(), 2);
+
+        press(&mut app, event::KeyCode::Char('U'));
+        assert!(app.filters.is_empty());
+        assert_eq!(app.view.height(), 4);
+
+        handle_key(
+            &mut app,
+            event::KeyEvent::new(event::KeyCode::Char('r'), event::KeyModifiers::CONTROL),
+        );
+        assert_eq!(app.filters.len(), 1);
+        assert_eq!(app.view.height(), 2);
+    }
+
+    #[test]
+    fn test_unique_value_with_space_filters_exactly() {
+        let df = df! { "city" => ["New York", "New York", "Boston", "York"] }.unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.state.select_column(Some(0));
+        press(&mut app, event::KeyCode::Char('u'));
+        assert_eq!(app.unique_values_filtered[0].0, "New York");
+        press(&mut app, event::KeyCode::Enter);
+        assert_eq!(app.filters.len(), 1);
+        assert_eq!(app.view.height(), 2);
+    }
+
+    #[test]
+    fn test_filters_wait_for_pivot_to_clear() {
+        let df = df! {
+            "region" => ["n", "n", "s"],
+            "year" => ["2023", "2024", "2023"],
+            "sales" => [1i64, 2, 3],
+        }
+        .unwrap();
+        let mut app = App::new(df, "test.csv".to_string());
+        app.filters.push(Filter::new(2, "> 1".to_string()));
+        app.update_filter();
+        app.pivot_setup.columns = 1;
+        app.pivot_setup.values = 2;
+        app.apply_pivot();
+        let pivot = app.view.clone();
+
+        press(&mut app, event::KeyCode::Char('f'));
+        assert!(matches!(app.mode, Mode::Normal));
+        press(&mut app, event::KeyCode::Char('F'));
+        press(&mut app, event::KeyCode::Char('L'));
+        assert!(matches!(app.mode, Mode::Normal));
+        assert!(matches!(app.status_message, Some(StatusMessage::Error(_))));
+        assert_eq!(app.filters.len(), 1);
+        assert!(app.view.equals_missing(&pivot));
+
+        press(&mut app, event::KeyCode::Char('P'));
+        press(&mut app, event::KeyCode::Char('F'));
+        assert!(app.filters.is_empty());
+        assert_eq!(app.view.height(), 3);
+    }
+}
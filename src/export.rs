@@ -0,0 +1,67 @@
+use polars::prelude::*;
+use std::fs::File;
+use std::path::Path;
+
+/// Write a frame to `file_path`, choosing the format from the extension.
+pub fn write_dataframe(
+    df: &mut DataFrame,
+    file_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ext = Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    // Check before creating the file so a typo doesn't leave an empty file behind.
+    if !matches!(
+        ext,
+        "csv" | "parquet" | "json" | "jsonl" | "ndjson" | "arrow" | "feather" | "ipc"
+    ) {
+        return Err(format!("Unsupported export format: .{}", ext).into());
+    }
+    let file = File::create(file_path)?;
+    match ext {
+        "csv" => CsvWriter::new(file).finish(df)?,
+        "parquet" => {
+            ParquetWriter::new(file).finish(df)?;
+        }
+        "json" => JsonWriter::new(file)
+            .with_json_format(JsonFormat::Json)
+            .finish(df)?,
+        "jsonl" | "ndjson" => JsonWriter::new(file)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(df)?,
+        _ => IpcWriter::new(file).finish(df)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CsvOptions;
+    use crate::loader::load_dataframe;
+
+    #[test]
+    fn test_round_trip_all_formats() {
+        let df = df! {
+            "name" => ["Alice", "Bob"],
+            "age"  => [30i64, 25],
+        }
+        .unwrap();
+        for ext in ["csv", "parquet", "json", "jsonl", "arrow"] {
+            let path = std::env::temp_dir().join(format!("iron_sight_export.{}", ext));
+            let path = path.to_string_lossy();
+            write_dataframe(&mut df.clone(), &path).unwrap();
+            let back = load_dataframe(&path, &CsvOptions::default()).unwrap();
+            assert!(back.equals(&df), "{}", ext);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_export_format() {
+        let path = std::env::temp_dir().join("iron_sight_export.xlsx");
+        let mut df = df! { "a" => [1i64] }.unwrap();
+        assert!(write_dataframe(&mut df, &path.to_string_lossy()).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,928 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta};
+use polars::prelude::*;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Gt,
+    Lt,
+    GtEq,
+    LtEq,
+    Eq,
+    NotEq,
+}
+
+impl CmpOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Gt => ">",
+            CmpOp::Lt => "<",
+            CmpOp::GtEq => ">=",
+            CmpOp::LtEq => "<=",
+            CmpOp::Eq => "=",
+            CmpOp::NotEq => "!=",
+        }
+    }
+
+    fn apply(self, lhs: Expr, rhs: Expr) -> Expr {
+        match self {
+            CmpOp::Gt => lhs.gt(rhs),
+            CmpOp::Lt => lhs.lt(rhs),
+            CmpOp::GtEq => lhs.gt_eq(rhs),
+            CmpOp::LtEq => lhs.lt_eq(rhs),
+            CmpOp::Eq => lhs.eq(rhs),
+            CmpOp::NotEq => lhs.neq(rhs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Op(CmpOp),
+    /// `~` regex match; `!~` negates it and `~*` ignores case.
+    Match {
+        negated: bool,
+        insensitive: bool,
+    },
+    Comma,
+    Word(String),
+    Quoted(String),
+    Column(String),
+}
+
+/// Split a query into tokens, each with the byte range of the query it came from.
+fn tokenize(input: &str) -> Result<Vec<(Token, Range<usize>)>, String> {
+    let mut tokens = Vec::new();
+    let mut spans = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    let mut token_start = 0;
+    while let Some(&(start, ch)) = chars.peek() {
+        // At most one token is pushed per pass; it ends where this one starts.
+        if spans.len() < tokens.len() {
+            spans.push(token_start..start);
+        }
+        token_start = start;
+        match ch {
+            _ if ch.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if ch == '(' {
+                    Token::LParen
+                } else {
+                    Token::RParen
+                });
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '~' => {
+                chars.next();
+                let insensitive = chars.next_if(|&(_, c)| c == '*').is_some();
+                tokens.push(Token::Match {
+                    negated: false,
+                    insensitive,
+                });
+            }
+            '>' | '<' | '=' | '!' => {
+                chars.next();
+                if ch == '!' && chars.next_if(|&(_, c)| c == '~').is_some() {
+                    let insensitive = chars.next_if(|&(_, c)| c == '*').is_some();
+                    tokens.push(Token::Match {
+                        negated: true,
+                        insensitive,
+                    });
+                    continue;
+                }
+                let eq = chars.next_if(|&(_, c)| c == '=').is_some();
+                tokens.push(match (ch, eq) {
+                    ('>', true) => Token::Op(CmpOp::GtEq),
+                    ('>', false) => Token::Op(CmpOp::Gt),
+                    ('<', true) => Token::Op(CmpOp::LtEq),
+                    ('<', false) => Token::Op(CmpOp::Lt),
+                    ('!', true) => Token::Op(CmpOp::NotEq),
+                    ('!', false) => Token::Not,
+                    // `=` and `==`
+                    _ => {
+                        if !eq {
+                            chars.next_if(|&(_, c)| c == '=');
+                        }
+                        Token::Op(CmpOp::Eq)
+                    }
+                });
+            }
+            '\'' | '"' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote stands for the quote itself, as in SQL.
+                        Some((_, c)) if c == ch && chars.next_if(|&(_, c)| c == ch).is_some() => {
+                            text.push(c)
+                        }
+                        Some((_, c)) if c == ch => break,
+                        Some((_, c)) => text.push(c),
+                        None => return Err(format!("missing closing {}", ch)),
+                    }
+                }
+                tokens.push(if ch == '`' {
+                    Token::Column(text)
+                } else {
+                    Token::Quoted(text)
+                });
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace()
+                        || matches!(c, '(' | ')' | ',' | '~' | '>' | '<' | '=' | '!')
+                    {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" | "&&" => Token::And,
+                    "or" | "||" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+    if spans.len() < tokens.len() {
+        spans.push(token_start..input.len());
+    }
+    Ok(tokens.into_iter().zip(spans).collect())
+}
+
+/// Words that start a predicate after a column, e.g. `between 1 and 5`.
+const PREDICATE_WORDS: [&str; 9] = [
+    "in",
+    "between",
+    "is",
+    "starts",
+    "ends",
+    "istarts",
+    "iends",
+    "contains",
+    "icontains",
+];
+
+/// The right-hand side of a comparison.
+enum Operand {
+    Column(String),
+    Literal(String),
+    Quoted(String),
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    spans: Vec<Range<usize>>, // where each token is in `query`
+    query: &'a str,
+    pos: usize,
+    column: &'a str,
+    schema: &'a Schema,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and_expr()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = expr.or(self.and_expr()?);
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not_expr()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = expr.and(self.not_expr()?);
+        }
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(self.not_expr()?.not());
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            None => Err("expected a condition".to_string()),
+            Some(Token::LParen) => {
+                self.next();
+                let expr = self.or_expr()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("missing closing )".to_string()),
+                }
+            }
+            _ if self.starts_predicate(self.pos) => self.predicate(self.column.to_string()),
+            Some(Token::Word(name) | Token::Column(name)) if self.has_lhs(name) => {
+                let name = name.clone();
+                self.next();
+                let lhs = self.column_name(name)?;
+                self.predicate(lhs)
+            }
+            Some(Token::Quoted(_)) if self.starts_predicate(self.pos + 1) => {
+                Err("a quoted string can't be compared; use a column".into())
+            }
+            Some(Token::Word(_) | Token::Quoted(_) | Token::Column(_)) => {
+                // The text is taken from the query as typed, so spacing and
+                // commas survive: `1,000` finds `1,000`. A lone quoted string
+                // loses its quotes.
+                let first = self.pos;
+                while matches!(
+                    self.peek(),
+                    Some(Token::Word(_) | Token::Quoted(_) | Token::Column(_) | Token::Comma)
+                ) {
+                    self.next();
+                }
+                let text = match &self.tokens[first..self.pos] {
+                    [Token::Quoted(text)] => text.clone(),
+                    _ => self.query[self.spans[first].start..self.spans[self.pos - 1].end]
+                        .to_string(),
+                };
+                Ok(col(self.column)
+                    .cast(DataType::String)
+                    .str()
+                    .contains_literal(lit(text)))
+            }
+            Some(token) => Err(format!("unexpected {}", describe(token))),
+        }
+    }
+
+    /// Whether the tokens at `i` start a predicate such as `> 3`, `~ ^A`,
+    /// `in (...)`, `is null` or `not between 1 and 2`. A keyword with nothing
+    /// after it is just text, so searching for `in` finds `main`.
+    fn starts_predicate(&self, i: usize) -> bool {
+        match self.tokens.get(i) {
+            Some(Token::Op(_) | Token::Match { .. }) => true,
+            Some(Token::Word(w)) => {
+                PREDICATE_WORDS.contains(&w.to_lowercase().as_str()) && self.has_operand(i + 1)
+            }
+            Some(Token::Not) => {
+                matches!(
+                    self.tokens.get(i + 1),
+                    Some(Token::Word(w)) if matches!(w.to_lowercase().as_str(), "in" | "between")
+                ) && self.has_operand(i + 2)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a keyword's operand can start at `i`.
+    fn has_operand(&self, i: usize) -> bool {
+        !matches!(
+            self.tokens.get(i),
+            None | Some(Token::And | Token::Or | Token::RParen)
+        )
+    }
+
+    /// Whether `name` (the current token) is a column on the left of a predicate.
+    /// A plain word before a keyword predicate only counts when it is a column,
+    /// so `this is it` stays a substring search.
+    fn has_lhs(&self, name: &str) -> bool {
+        match self.tokens.get(self.pos + 1) {
+            Some(Token::Op(_) | Token::Match { .. }) => true,
+            _ => {
+                self.starts_predicate(self.pos + 1)
+                    && (self.schema.contains(name) || matches!(self.peek(), Some(Token::Column(_))))
+            }
+        }
+    }
+
+    fn predicate(&mut self, lhs: String) -> Result<Expr, String> {
+        let negated = self.peek() == Some(&Token::Not);
+        if negated {
+            self.next();
+        }
+        let expr = match self.next() {
+            Some(Token::Op(op)) => {
+                let rhs = self.operand(op.symbol())?;
+                self.compare(&lhs, op, rhs)?
+            }
+            Some(Token::Match {
+                negated,
+                insensitive,
+            }) => {
+                let pattern = self.text("~")?;
+                let pattern = if insensitive {
+                    format!("(?i){}", pattern)
+                } else {
+                    pattern
+                };
+                let expr = col(&lhs)
+                    .cast(DataType::String)
+                    .str()
+                    .contains(lit(pattern), true);
+                if negated {
+                    expr.not()
+                } else {
+                    expr
+                }
+            }
+            Some(Token::Word(w)) => match w.to_lowercase().as_str() {
+                "in" if self.peek() != Some(&Token::LParen) => {
+                    // `in 2026-09`: a calendar period on a date column.
+                    let text = self.text("in")?;
+                    let (start, end) =
+                        parse_period(&text).ok_or_else(|| format!("`{}` is not a period", text))?;
+                    let dtype = self.temporal_dtype(&lhs)?;
+                    col(&lhs)
+                        .gt_eq(temporal_lit(start, dtype))
+                        .and(col(&lhs).lt(temporal_lit(end, dtype)))
+                }
+                "in" => {
+                    self.next();
+                    let first = self.operand("in (")?;
+                    let mut expr = self.compare(&lhs, CmpOp::Eq, first)?;
+                    loop {
+                        match self.next() {
+                            Some(Token::Comma) => {
+                                let rhs = self.operand(",")?;
+                                expr = expr.or(self.compare(&lhs, CmpOp::Eq, rhs)?);
+                            }
+                            Some(Token::RParen) => break,
+                            _ => return Err("missing closing )".to_string()),
+                        }
+                    }
+                    expr
+                }
+                "between" => {
+                    let low = self.operand("between")?;
+                    if self.next() != Some(Token::And) {
+                        return Err("expected `and` in between".to_string());
+                    }
+                    let high = self.operand("and")?;
+                    self.compare(&lhs, CmpOp::GtEq, low)?.and(self.compare(
+                        &lhs,
+                        CmpOp::LtEq,
+                        high,
+                    )?)
+                }
+                "is" => {
+                    let is_not = self.peek() == Some(&Token::Not);
+                    if is_not {
+                        self.next();
+                    }
+                    match self.next() {
+                        Some(Token::Word(w)) if w.eq_ignore_ascii_case("null") => {}
+                        _ => return Err("expected null after is".to_string()),
+                    }
+                    if is_not {
+                        col(&lhs).is_not_null()
+                    } else {
+                        col(&lhs).is_null()
+                    }
+                }
+                kw @ ("starts" | "ends" | "istarts" | "iends") => {
+                    match self.next() {
+                        Some(Token::Word(w)) if w.eq_ignore_ascii_case("with") => {}
+                        _ => return Err(format!("expected with after {}", kw)),
+                    }
+                    let insensitive = kw.starts_with('i');
+                    let text = self.text("with")?;
+                    let (value, text) = self.case(&lhs, text, insensitive);
+                    if kw.ends_with("starts") {
+                        value.str().starts_with(lit(text))
+                    } else {
+                        value.str().ends_with(lit(text))
+                    }
+                }
+                kw => {
+                    // `contains` / `icontains`
+                    let text = self.text(kw)?;
+                    let (value, text) = self.case(&lhs, text, kw.starts_with('i'));
+                    value.str().contains_literal(lit(text))
+                }
+            },
+            Some(token) => return Err(format!("unexpected {}", describe(&token))),
+            None => return Err("expected a condition".to_string()),
+        };
+        Ok(if negated { expr.not() } else { expr })
+    }
+
+    /// The column as strings, lowercased along with `text` when ignoring case.
+    fn case(&self, lhs: &str, text: String, insensitive: bool) -> (Expr, String) {
+        let value = col(lhs).cast(DataType::String);
+        if insensitive {
+            (value.str().to_lowercase(), text.to_lowercase())
+        } else {
+            (value, text)
+        }
+    }
+
+    fn operand(&mut self, after: &str) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Word(w)) if self.schema.contains(&w) => Ok(Operand::Column(w)),
+            Some(Token::Word(w)) => Ok(Operand::Literal(w)),
+            Some(Token::Quoted(s)) => Ok(Operand::Quoted(s)),
+            Some(Token::Column(name)) => Ok(Operand::Column(self.column_name(name)?)),
+            _ => Err(format!("expected a value after {}", after)),
+        }
+    }
+
+    /// A literal string argument, e.g. a regex or prefix.
+    fn text(&mut self, after: &str) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(w) | Token::Quoted(w)) => Ok(w),
+            _ => Err(format!("expected a value after {}", after)),
+        }
+    }
+
+    fn column_name(&self, name: String) -> Result<String, String> {
+        if self.schema.contains(&name) {
+            Ok(name)
+        } else {
+            Err(format!("no column named `{}`", name))
+        }
+    }
+
+    /// Numbers compare numerically against numeric columns, and dates, times
+    /// and durations natively against temporal ones. Everything else compares
+    /// as strings, so `= Engineering` works on any column.
+    fn compare(&self, lhs: &str, op: CmpOp, rhs: Operand) -> Result<Expr, String> {
+        let dtype = self.schema.get(lhs);
+        let text = match rhs {
+            Operand::Column(name) => return Ok(op.apply(col(lhs), col(&name))),
+            Operand::Literal(text) | Operand::Quoted(text) => text,
+        };
+        match dtype {
+            Some(DataType::Date | DataType::Datetime(..)) => {
+                let value = parse_instant(&text, chrono::Local::now().naive_local())
+                    .ok_or_else(|| format!("`{}` is not a date", text))?;
+                Ok(op.apply(col(lhs), temporal_lit(value, dtype.unwrap())))
+            }
+            Some(DataType::Duration(unit)) => {
+                let value =
+                    parse_duration(&text).ok_or_else(|| format!("`{}` is not a duration", text))?;
+                Ok(op.apply(col(lhs), duration_lit(value, *unit)))
+            }
+            Some(dtype) if dtype.is_primitive_numeric() => {
+                // Whole numbers stay integers against integer columns, so a scan
+                // can skip hive partitions by their key.
+                let value = match (text.parse::<i64>(), text.parse::<f64>()) {
+                    (Ok(value), _) if dtype.is_integer() => lit(value),
+                    (_, Ok(value)) => lit(value),
+                    _ => return Ok(op.apply(col(lhs).cast(DataType::String), lit(text))),
+                };
+                Ok(op.apply(col(lhs), value))
+            }
+            _ => Ok(op.apply(col(lhs).cast(DataType::String), lit(text))),
+        }
+    }
+
+    fn temporal_dtype(&self, name: &str) -> Result<&DataType, String> {
+        match self.schema.get(name) {
+            Some(dtype @ (DataType::Date | DataType::Datetime(..))) => Ok(dtype),
+            _ => Err(format!("`{}` is not a date column", name)),
+        }
+    }
+}
+
+/// Parse an ISO date or timestamp, or `now` / `today` with an optional offset
+/// such as `now-7d` or `today+1w`.
+fn parse_instant(text: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let lower = text.to_lowercase();
+    for (name, base) in [("now", now), ("today", now.date().and_hms_opt(0, 0, 0)?)] {
+        if let Some(rest) = lower.strip_prefix(name) {
+            return match rest.chars().next() {
+                None => Some(base),
+                Some('+') => base.checked_add_signed(parse_duration(&rest[1..])?),
+                Some('-') => base.checked_sub_signed(parse_duration(&rest[1..])?),
+                Some(_) => None,
+            };
+        }
+    }
+    const FORMATS: [&str; 4] = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ];
+    FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })
+}
+
+/// Parse `2026`, `2026-09`, `2026-09-15` or `today` into a half-open range.
+fn parse_period(text: &str) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let parts: Vec<&str> = text.split('-').collect();
+    let (start, end) = if text.eq_ignore_ascii_case("today") {
+        let today = chrono::Local::now().date_naive();
+        (today, today.succ_opt()?)
+    } else {
+        match parts[..] {
+            [y] if y.len() == 4 => {
+                let y = y.parse().ok()?;
+                (
+                    NaiveDate::from_ymd_opt(y, 1, 1)?,
+                    NaiveDate::from_ymd_opt(y + 1, 1, 1)?,
+                )
+            }
+            [y, m] => {
+                let (y, m): (i32, u32) = (y.parse().ok()?, m.parse().ok()?);
+                let next = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+                (
+                    NaiveDate::from_ymd_opt(y, m, 1)?,
+                    NaiveDate::from_ymd_opt(next.0, next.1, 1)?,
+                )
+            }
+            [_, _, _] => {
+                let day = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+                (day, day.succ_opt()?)
+            }
+            _ => return None,
+        }
+    };
+    Some((start.and_hms_opt(0, 0, 0)?, end.and_hms_opt(0, 0, 0)?))
+}
+
+/// Parse a duration such as `90s`, `2h`, `1h30m` or `7d`. Units: `ms`, `s`,
+/// `m`, `h`, `d`, `w`.
+fn parse_duration(text: &str) -> Option<TimeDelta> {
+    let mut total = TimeDelta::zero();
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let n: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..unit_len] {
+            "ms" => TimeDelta::try_milliseconds(n)?,
+            "s" => TimeDelta::try_seconds(n)?,
+            "m" => TimeDelta::try_minutes(n)?,
+            "h" => TimeDelta::try_hours(n)?,
+            "d" => TimeDelta::try_days(n)?,
+            "w" => TimeDelta::try_weeks(n)?,
+            _ => return None,
+        };
+        total = total.checked_add(&part)?;
+        rest = &rest[unit_len..];
+    }
+    Some(total)
+}
+
+/// A literal of the column's temporal type. Datetimes are taken as UTC.
+fn temporal_lit(value: NaiveDateTime, dtype: &DataType) -> Expr {
+    let since_epoch = value - DateTime::UNIX_EPOCH.naive_utc();
+    match dtype {
+        DataType::Datetime(unit, _) => {
+            let n = match unit {
+                TimeUnit::Milliseconds => since_epoch.num_milliseconds(),
+                TimeUnit::Microseconds => since_epoch.num_microseconds().unwrap_or(i64::MAX),
+                TimeUnit::Nanoseconds => since_epoch.num_nanoseconds().unwrap_or(i64::MAX),
+            };
+            lit(n).cast(dtype.clone())
+        }
+        // Dates compare at day resolution, so `> now-7d` starts at midnight.
+        _ => lit(since_epoch.num_days() as i32).cast(DataType::Date),
+    }
+}
+
+fn duration_lit(value: TimeDelta, unit: TimeUnit) -> Expr {
+    let n = match unit {
+        TimeUnit::Milliseconds => value.num_milliseconds(),
+        TimeUnit::Microseconds => value.num_microseconds().unwrap_or(i64::MAX),
+        TimeUnit::Nanoseconds => value.num_nanoseconds().unwrap_or(i64::MAX),
+    };
+    lit(n).cast(DataType::Duration(unit))
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::LParen => "(".to_string(),
+        Token::RParen => ")".to_string(),
+        Token::And => "`and`".to_string(),
+        Token::Or => "`or`".to_string(),
+        Token::Not => "`not`".to_string(),
+        Token::Op(op) => op.symbol().to_string(),
+        Token::Match { .. } => "~".to_string(),
+        Token::Comma => ",".to_string(),
+        Token::Word(w) | Token::Quoted(w) | Token::Column(w) => format!("`{}`", w),
+    }
+}
+
+/// Compile a filter typed on `col_name` into a polars expression. `schema`
+/// resolves column references and decides numeric vs string comparison.
+///
+/// ```text
+/// > 30                       comparison against the selected column
+/// = eng or = hr              `and` / `or` / `not` (also `&&`, `||`, `!`)
+/// (> 10 and < 20) or = 0     parentheses
+/// revenue > cost             another column on either side
+/// `unit price` >= 9.5        backticks for column names with spaces
+/// = 'new york'               quotes force a string literal
+/// ~ ^A.*e$                   regex match (`~*` ignores case, `!~` negates)
+/// in (eng, hr, 'ops team')   any of a list
+/// between 10 and 20          inclusive range
+/// is null / is not null      missing values
+/// starts with Al             also `ends with`, `contains`; prefix `i` to ignore
+///                            case: `istarts with`, `iends with`, `icontains`
+/// > 2026-01-01              dates, timestamps and `now` / `today` offsets
+/// > now-7d                   (`ms s m h d w`) on date and datetime columns
+/// in 2026-09                 a year, month or day on a date column
+/// > 1h30m                    durations on duration columns
+/// li                         anything else without an operator is a substring match
+/// ```
+///
+/// `not` also works before `in` and `between`: `dept not in (eng, hr)`.
+///
+/// A bare word on the right of an operator refers to a column when one has that
+/// name, and is a literal otherwise.
+pub fn build_filter_expr(col_name: &str, query: &str, schema: &Schema) -> Result<Expr, String> {
+    let (tokens, spans) = tokenize(query)?.into_iter().unzip();
+    let mut parser = Parser {
+        tokens,
+        spans,
+        query,
+        pos: 0,
+        column: col_name,
+        schema,
+    };
+    let expr = parser.or_expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {}", describe(token))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(column: &str, query: &str) -> Result<usize, String> {
+        let df = df! {
+            "name"    => ["Alice", "Bob", "Charlie", "Dana"],
+            "dept"    => ["eng", "hr", "eng", "ops"],
+            "revenue" => [10i64, 20, 30, 40],
+            "cost"    => [15i64, 5, 30, 10],
+            "email"   => [Some("a@x.com"), None, Some("c@y.org"), None],
+            "amount"  => ["1,000", "25,000", "a.b", "axb"],
+        }
+        .unwrap();
+        let expr = build_filter_expr(column, query, df.schema())?;
+        Ok(df.lazy().filter(expr).collect().unwrap().height())
+    }
+
+    #[test]
+    fn test_or() {
+        assert_eq!(count("dept", "= eng or = hr"), Ok(3));
+        assert_eq!(count("name", "dept = eng || dept = ops"), Ok(3));
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        assert_eq!(count("revenue", "> 35 or > 15 and < 25"), Ok(2)); // Bob, Dana
+        assert_eq!(count("revenue", "(> 35 or > 15) and < 25"), Ok(1)); // Bob
+    }
+
+    #[test]
+    fn test_not() {
+        assert_eq!(count("dept", "not = eng"), Ok(2));
+        assert_eq!(count("dept", "!(= eng or = hr)"), Ok(1));
+    }
+
+    #[test]
+    fn test_cross_column() {
+        assert_eq!(count("name", "revenue > cost"), Ok(2)); // Bob, Dana
+        assert_eq!(count("revenue", ">= `cost`"), Ok(3));
+    }
+
+    #[test]
+    fn test_quoted_literal_and_substring() {
+        assert_eq!(count("name", "= 'Bob'"), Ok(1));
+        assert_eq!(count("name", "li"), Ok(2));
+        assert_eq!(count("amount", "1,000"), Ok(1));
+        assert_eq!(count("amount", "5,000"), Ok(1));
+        assert_eq!(count("amount", "a.b"), Ok(1)); // not a regex
+        assert_eq!(count("amount", "'a.b'"), Ok(1));
+        assert_eq!(count("dept", "= 'cost'"), Ok(0));
+        assert_eq!(count("name", "= 'Bob''s'"), Ok(0));
+        let quoted = |query: &str| tokenize(query).unwrap()[0].0.clone();
+        assert_eq!(quoted("'it''s'"), Token::Quoted("it's".to_string()));
+        assert_eq!(
+            quoted(r#""say ""hi"" it's""#),
+            Token::Quoted(r#"say "hi" it's"#.to_string())
+        );
+        assert_eq!(quoted("''"), Token::Quoted(String::new()));
+    }
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(count("name", "~ ^[AB]"), Ok(2));
+        assert_eq!(count("name", "~ '^(alice|dana)$'"), Ok(0));
+        assert_eq!(count("name", "~* '^(alice|dana)$'"), Ok(2));
+        assert_eq!(count("name", "!~ e$"), Ok(2)); // Bob, Dana
+        assert_eq!(count("name", "dept ~ ^e"), Ok(2));
+    }
+
+    #[test]
+    fn test_in_list() {
+        assert_eq!(count("dept", "in (eng, hr)"), Ok(3));
+        assert_eq!(count("dept", "not in (eng, hr)"), Ok(1));
+        assert_eq!(count("name", "dept not in (eng)"), Ok(2));
+        assert_eq!(count("revenue", "in (10, 40, 99)"), Ok(2));
+    }
+
+    #[test]
+    fn test_between() {
+        assert_eq!(count("revenue", "between 15 and 30"), Ok(2));
+        assert_eq!(count("name", "cost between 5 and 10 or = Alice"), Ok(3));
+        assert_eq!(count("revenue", "not between 15 and 30"), Ok(2));
+    }
+
+    #[test]
+    fn test_null_checks() {
+        assert_eq!(count("email", "is null"), Ok(2));
+        assert_eq!(count("email", "is not null"), Ok(2));
+        assert_eq!(count("name", "email is null and revenue > 20"), Ok(1)); // Dana
+    }
+
+    #[test]
+    fn test_starts_ends_contains() {
+        assert_eq!(count("name", "starts with Al"), Ok(1));
+        assert_eq!(count("name", "starts with al"), Ok(0));
+        assert_eq!(count("name", "istarts with al"), Ok(1));
+        assert_eq!(count("email", "ends with .org"), Ok(1));
+        assert_eq!(count("name", "contains AR"), Ok(0));
+        assert_eq!(count("name", "icontains AR"), Ok(1));
+        assert_eq!(count("name", "this is it"), Ok(0)); // plain substring
+    }
+
+    #[test]
+    fn test_bare_keyword_is_a_substring() {
+        let df = df! { "k" => ["is in", "contains", "starts ends", "between"] }.unwrap();
+        let count = |query: &str| {
+            let expr = build_filter_expr("k", query, df.schema())?;
+            Ok::<_, String>(df.clone().lazy().filter(expr).collect().unwrap().height())
+        };
+        assert_eq!(count("in"), Ok(2));
+        assert_eq!(count("IS"), Ok(0)); // case-sensitive, like any substring
+        assert_eq!(count("is"), Ok(1));
+        assert_eq!(count("between"), Ok(1));
+        assert_eq!(count("contains"), Ok(1));
+        assert_eq!(count("starts"), Ok(1));
+        assert_eq!(count("ends"), Ok(1));
+        assert_eq!(count("not in"), Ok(2));
+        assert_eq!(count("k in"), Ok(0)); // the whole text, not `k in ...`
+        assert_eq!(count("in or = between"), Ok(3));
+        assert_eq!(count("(is)"), Ok(1));
+    }
+
+    fn count_dates(column: &str, query: &str) -> Result<usize, String> {
+        let df = df! {
+            "day"  => ["2026-08-31", "2026-09-01", "2026-09-30", "2026-10-01"],
+            "at"   => ["2026-09-01 08:00:00", "2026-09-01 12:30:00", "2026-09-02 00:00:00", "2027-01-01 00:00:00"],
+            "took" => [30i64, 90, 3600, 7200],
+        }
+        .unwrap()
+        .lazy()
+        .with_columns([
+            col("day").cast(DataType::Date),
+            col("at").str().to_datetime(
+                Some(TimeUnit::Microseconds),
+                None,
+                StrptimeOptions::default(),
+                lit("raise"),
+            ),
+            col("took").cast(DataType::Duration(TimeUnit::Milliseconds)) * lit(1000),
+        ])
+        .collect()
+        .unwrap();
+        let expr = build_filter_expr(column, query, df.schema())?;
+        Ok(df.lazy().filter(expr).collect().unwrap().height())
+    }
+
+    #[test]
+    fn test_date_comparisons() {
+        assert_eq!(count_dates("day", ">= 2026-09-01"), Ok(3));
+        assert_eq!(
+            count_dates("day", "between 2026-09-01 and 2026-09-30"),
+            Ok(2)
+        );
+        assert_eq!(count_dates("at", "> 2026-09-01T12:00"), Ok(3));
+        assert_eq!(count_dates("at", "< '2026-09-01 12:30:00'"), Ok(1));
+        assert_eq!(count_dates("day", "> now+1000d"), Ok(0));
+        assert_eq!(count_dates("at", "< now+5000d"), Ok(4));
+    }
+
+    #[test]
+    fn test_date_periods() {
+        assert_eq!(count_dates("day", "in 2026-09"), Ok(2));
+        assert_eq!(count_dates("day", "not in 2026-09"), Ok(2));
+        assert_eq!(count_dates("at", "in 2026-09-01"), Ok(2));
+        assert_eq!(count_dates("at", "in 2026"), Ok(3));
+        assert_eq!(count_dates("at", "in 2026-12 or in 2027-01"), Ok(1));
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(count_dates("took", "> 1m"), Ok(3));
+        assert_eq!(count_dates("took", "<= 1h"), Ok(3));
+        assert_eq!(count_dates("took", "between 1m30s and 1h"), Ok(2));
+    }
+
+    #[test]
+    fn test_parse_instant() {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 17)
+            .unwrap()
+            .and_hms_opt(15, 30, 0)
+            .unwrap();
+        let at = |d: u32, h: u32, m: u32| {
+            NaiveDate::from_ymd_opt(2026, 10, d)
+                .unwrap()
+                .and_hms_opt(h, m, 0)
+                .unwrap()
+        };
+        assert_eq!(parse_instant("now-7d", now), Some(at(10, 15, 30)));
+        assert_eq!(parse_instant("today", now), Some(at(17, 0, 0)));
+        assert_eq!(parse_instant("today+1w", now), Some(at(24, 0, 0)));
+        assert_eq!(parse_instant("NOW-1h30m", now), Some(at(17, 14, 0)));
+        assert_eq!(parse_instant("2026-10-01", now), Some(at(1, 0, 0)));
+        assert_eq!(parse_instant("now-7x", now), None);
+        assert_eq!(parse_instant("yesterday", now), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            count("dept", "(= eng or = hr"),
+            Err("missing closing )".to_string())
+        );
+        assert_eq!(
+            count("dept", "= eng or"),
+            Err("expected a condition".to_string())
+        );
+        assert_eq!(
+            count("revenue", ">"),
+            Err("expected a value after >".to_string())
+        );
+        assert_eq!(
+            count("name", "salary > 3"),
+            Err("no column named `salary`".to_string())
+        );
+        assert_eq!(
+            count("name", "= 'Bob"),
+            Err("missing closing '".to_string())
+        );
+        assert_eq!(count("name", "= Bob)"), Err("unexpected )".to_string()));
+        assert_eq!(
+            count("dept", "in (eng, hr"),
+            Err("missing closing )".to_string())
+        );
+        assert_eq!(
+            count("cost", "between 1 or 2"),
+            Err("expected `and` in between".to_string())
+        );
+        assert_eq!(
+            count("email", "is empty"),
+            Err("expected null after is".to_string())
+        );
+        assert_eq!(
+            count_dates("day", "> tomorrow"),
+            Err("`tomorrow` is not a date".to_string())
+        );
+        assert_eq!(
+            count("name", "in 2026-09"),
+            Err("`name` is not a date column".to_string())
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::app::{App, Filter, SortDirection, SortKey};
+use crate::cli::QueryOptions;
+use crate::export::write_dataframe;
+use polars::prelude::*;
+
+/// Apply the command-line filters, group-by and sort to `app`, the same way the
+/// TUI does, and return the resulting frame.
+pub fn apply_query(
+    app: &mut App,
+    query: &QueryOptions,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    for input in &query.computes {
+        app.compute_input = input.clone();
+        if !app.add_computed_column() {
+            let err = app.compute_error.take().unwrap_or_default();
+            return Err(format!("invalid column `{}`: {}", input, err).into());
+        }
+    }
+    app.filters = query
+        .filters
+        .iter()
+        .map(|(name, q)| Ok(Filter::new(column_index(&app.headers, name)?, q.clone())))
+        .collect::<Result<_, String>>()?;
+    app.update_filter();
+    if let Some(err) = app.filter_error.take() {
+        return Err(format!("invalid filter: {}", err).into());
+    }
+
+    if !query.group_by.is_empty() {
+        for name in &query.group_by {
+            let idx = column_index(&app.headers, name)?;
+            if !app.groupby_keys.contains(&idx) {
+                app.groupby_keys.push(idx);
+            }
+        }
+        for (name, func) in &query.aggs {
+            let idx = column_index(&app.headers, name)?;
+            let funcs = app.groupby_aggs.entry(idx).or_default();
+            if !funcs.contains(func) {
+                funcs.push(func.clone());
+            }
+        }
+        app.apply_groupby();
+        if !app.groupby_active {
+            return Err("group-by failed".into());
+        }
+    }
+
+    // After a group-by the sort columns refer to the result, e.g. `salary_mean`.
+    if !query.sorts.is_empty() {
+        app.sort_keys = query
+            .sorts
+            .iter()
+            .map(|(name, descending)| {
+                let direction = if *descending {
+                    SortDirection::Descending
+                } else {
+                    SortDirection::Ascending
+                };
+                Ok(SortKey::new(column_index(&app.headers, name)?, direction))
+            })
+            .collect::<Result<_, String>>()?;
+        app.apply_sort();
+    }
+    Ok(app.collect_view()?)
+}
+
+/// Run the query and write the result to `--output`, or as CSV to stdout.
+pub fn run(mut app: App, query: &QueryOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut df = apply_query(&mut app, query)?;
+    match &query.output {
+        Some(path) => write_dataframe(&mut df, path),
+        None => {
+            CsvWriter::new(std::io::stdout().lock()).finish(&mut df)?;
+            Ok(())
+        }
+    }
+}
+
+fn column_index(headers: &[String], name: &str) -> Result<usize, String> {
+    headers
+        .iter()
+        .position(|h| h == name)
+        .ok_or_else(|| format!("no column named `{}`", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::AggFunc;
+
+    fn make_app() -> App {
+        let df = df! {
+            "name" => ["Alice", "Bob", "Charlie", "Dana"],
+            "dept" => ["eng", "hr", "eng", "eng"],
+            "age"  => [30i64, 25, 35, 41],
+            "sal"  => [100i64, 80, 200, 300],
+        }
+        .unwrap();
+        App::new(df, "test.csv".to_string())
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let mut app = make_app();
+        let query = QueryOptions {
+            filters: vec![("age".to_string(), "> 26".to_string())],
+            sorts: vec![("age".to_string(), true)],
+            ..Default::default()
+        };
+        let df = apply_query(&mut app, &query).unwrap();
+        let names: Vec<_> = df
+            .column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(names, vec![Some("Dana"), Some("Charlie"), Some("Alice")]);
+    }
+
+    #[test]
+    fn test_group_by_then_sort_result() {
+        let mut app = make_app();
+        let query = QueryOptions {
+            group_by: vec!["dept".to_string()],
+            aggs: vec![("sal".to_string(), AggFunc::Sum)],
+            sorts: vec![("sal_sum".to_string(), false)],
+            ..Default::default()
+        };
+        let df = apply_query(&mut app, &query).unwrap();
+        let depts: Vec<_> = df
+            .column("dept")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(depts, vec![Some("hr"), Some("eng")]);
+        let sums: Vec<_> = df
+            .column("sal_sum")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(sums, vec![Some(80), Some(600)]);
+    }
+
+    #[test]
+    fn test_multi_key_sort() {
+        let mut app = make_app();
+        let query = QueryOptions {
+            sorts: vec![("dept".to_string(), false), ("sal".to_string(), true)],
+            ..Default::default()
+        };
+        let df = apply_query(&mut app, &query).unwrap();
+        let names: Vec<_> = df
+            .column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            names,
+            vec![Some("Dana"), Some("Charlie"), Some("Alice"), Some("Bob")]
+        );
+    }
+
+    #[test]
+    fn test_computed_column() {
+        let mut app = make_app();
+        let query = QueryOptions {
+            computes: vec!["per_year = sal / age".to_string()],
+            filters: vec![("per_year".to_string(), "> 5".to_string())],
+            ..Default::default()
+        };
+        let df = apply_query(&mut app, &query).unwrap();
+        assert_eq!(df.width(), 5);
+        assert_eq!(df.height(), 2); // Charlie and Dana
+
+        let mut app = make_app();
+        let query = QueryOptions {
+            computes: vec!["x = nope + 1".to_string()],
+            ..Default::default()
+        };
+        let err = apply_query(&mut app, &query).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid column `x = nope + 1`: no column named `nope`"
+        );
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let mut app = make_app();
+        let query = QueryOptions {
+            filters: vec![("salary".to_string(), "> 1".to_string())],
+            ..Default::default()
+        };
+        let err = apply_query(&mut app, &query).unwrap_err();
+        assert_eq!(err.to_string(), "no column named `salary`");
+    }
+
+    #[test]
+    fn test_invalid_filter() {
+        let mut app = make_app();
+        let query = QueryOptions {
+            filters: vec![("age".to_string(), "(> 1".to_string())],
+            ..Default::default()
+        };
+        let err = apply_query(&mut app, &query).unwrap_err();
+        assert_eq!(err.to_string(), "invalid filter: missing closing )");
+    }
+}
@@ -0,0 +1,771 @@
+use crate::cli::{CsvOptions, Encoding};
+use polars::prelude::*;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+
+const ARROW_MAGIC: &[u8] = b"ARROW1";
+const ARROW_STREAM_MAGIC: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF];
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+const CSV_DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+const DEFAULT_INFER_SCHEMA_LENGTH: usize = 100;
+/// How much of a CSV file is read to find its header line for delimiter detection.
+const HEADER_SNIFF_BYTES: u64 = 64 * 1024;
+
+/// Path that stands for standard input on the command line.
+pub const STDIN_PATH: &str = "-";
+/// Column added with `--source-column` naming the file each row came from.
+pub const SOURCE_FILE_COLUMN: &str = "__source_file";
+
+/// The data format extension, looking through a compression suffix:
+/// `data.csv.gz` gives `csv`.
+fn extension(file_path: &str) -> &str {
+    let path = Path::new(file_path);
+    let path = match compression(file_path) {
+        Some(_) => Path::new(path.file_stem().unwrap_or_default()),
+        None => path,
+    };
+    path.extension().and_then(|e| e.to_str()).unwrap_or("")
+}
+
+/// The compression suffix (`gz`, `zst` or `bz2`), if any.
+fn compression(file_path: &str) -> Option<&str> {
+    Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|ext| matches!(*ext, "gz" | "zst" | "bz2"))
+}
+
+fn decompress(file_path: &str, codec: &str) -> std::io::Result<Vec<u8>> {
+    let file = std::fs::File::open(file_path)?;
+    let mut reader: Box<dyn Read> = match codec {
+        "gz" => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        "zst" => Box::new(zstd::stream::read::Decoder::new(file)?),
+        _ => Box::new(bzip2::read::MultiBzDecoder::new(file)),
+    };
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Expand glob patterns into sorted file paths. Shells usually do this already,
+/// but quoted patterns (`'events_*.parquet'`) reach us verbatim.
+pub fn expand_paths(patterns: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            paths.push(pattern.clone());
+            continue;
+        }
+        let mut matched: Vec<String> = glob::glob(pattern)?
+            .filter_map(Result::ok)
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        if matched.is_empty() {
+            return Err(format!("No files match {}", pattern).into());
+        }
+        matched.sort();
+        paths.extend(matched);
+    }
+    Ok(paths)
+}
+
+/// Read several files into one table, stacked vertically. Columns missing from
+/// a file are filled with nulls and mismatched types are widened.
+pub fn load_files(
+    paths: &[String],
+    opts: &CsvOptions,
+    source_column: bool,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let frames = paths
+        .iter()
+        .map(|path| {
+            Ok(tag_source(
+                load_dataframe(path, opts)?.lazy(),
+                path,
+                source_column,
+            ))
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    Ok(concat_frames(frames)?.collect()?)
+}
+
+/// Lazy counterpart of [`load_files`].
+pub fn scan_files(
+    paths: &[String],
+    opts: &CsvOptions,
+    source_column: bool,
+) -> Result<LazyFrame, Box<dyn std::error::Error>> {
+    let frames = paths
+        .iter()
+        .map(|path| Ok(tag_source(scan_file(path, opts)?, path, source_column)))
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    Ok(concat_frames(frames)?)
+}
+
+fn tag_source(lf: LazyFrame, path: &str, source_column: bool) -> LazyFrame {
+    if source_column {
+        lf.select([lit(path).alias(SOURCE_FILE_COLUMN), all()])
+    } else {
+        lf
+    }
+}
+
+fn concat_frames(mut frames: Vec<LazyFrame>) -> PolarsResult<LazyFrame> {
+    if frames.len() == 1 {
+        return Ok(frames.remove(0));
+    }
+    concat_lf_diagonal(
+        frames,
+        UnionArgs {
+            to_supertypes: true,
+            ..Default::default()
+        },
+    )
+}
+
+/// Read the whole file into memory.
+pub fn load_dataframe(
+    file_path: &str,
+    opts: &CsvOptions,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    if Path::new(file_path).is_dir() {
+        return Ok(scan_hive_dataset(file_path)?.collect()?);
+    }
+    if let Some(codec) = compression(file_path) {
+        let bytes = decompress(file_path, codec)?;
+        return match extension(file_path) {
+            "" => load_bytes(bytes, opts),
+            ext => parse_bytes(ext, bytes, opts),
+        };
+    }
+    let df = match extension(file_path) {
+        // polars only decodes UTF-8, so Latin-1 is transcoded in memory first.
+        "csv" if opts.encoding == Encoding::Latin1 => {
+            return parse_bytes("csv", std::fs::read(file_path)?, opts)
+        }
+        "csv" => csv_read_options(opts, file_delimiter(file_path, opts)?)
+            .try_into_reader_with_file_path(Some(file_path.into()))?
+            .finish()?,
+        "parquet" => ParquetReader::new(std::fs::File::open(file_path)?).finish()?,
+        "json" => read_json(file_path, JsonFormat::Json)?,
+        "jsonl" | "ndjson" => read_json(file_path, JsonFormat::JsonLines)?,
+        "arrow" | "feather" | "ipc" => read_ipc(file_path)?,
+        "" => return load_bytes(std::fs::read(file_path)?, opts),
+        ext => return Err(format!("Unsupported file format: .{}", ext).into()),
+    };
+    Ok(flatten_structs(df.lazy())?.collect()?)
+}
+
+/// Read everything piped into standard input.
+pub fn load_stdin(opts: &CsvOptions) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut bytes)?;
+    load_bytes(bytes, opts)
+}
+
+/// Parse an in-memory buffer whose format is detected from its content.
+pub fn load_bytes(
+    bytes: Vec<u8>,
+    opts: &CsvOptions,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    parse_bytes(sniff_format(&bytes), bytes, opts)
+}
+
+/// Parse an in-memory buffer in the format named by `ext`.
+fn parse_bytes(
+    ext: &str,
+    bytes: Vec<u8>,
+    opts: &CsvOptions,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let df = match ext {
+        "parquet" => ParquetReader::new(Cursor::new(bytes)).finish()?,
+        "arrow" | "feather" | "ipc" if bytes.starts_with(ARROW_MAGIC) => {
+            IpcReader::new(Cursor::new(bytes)).finish()?
+        }
+        "arrow" | "feather" | "ipc" => IpcStreamReader::new(Cursor::new(bytes)).finish()?,
+        "json" => JsonReader::new(Cursor::new(bytes))
+            .with_json_format(JsonFormat::Json)
+            .finish()?,
+        "jsonl" | "ndjson" => JsonReader::new(Cursor::new(bytes))
+            .with_json_format(JsonFormat::JsonLines)
+            .finish()?,
+        "csv" => {
+            let bytes = match opts.encoding {
+                Encoding::Latin1 => latin1_to_utf8(&bytes),
+                _ => bytes,
+            };
+            let separator = opts.delimiter.unwrap_or_else(|| detect_delimiter(&bytes));
+            csv_read_options(opts, separator)
+                .into_reader_with_file_handle(Cursor::new(bytes))
+                .finish()?
+        }
+        ext => return Err(format!("Unsupported file format: .{}", ext).into()),
+    };
+    Ok(flatten_structs(df.lazy())?.collect()?)
+}
+
+fn csv_read_options(opts: &CsvOptions, separator: u8) -> CsvReadOptions {
+    CsvReadOptions::default()
+        .with_has_header(!opts.no_header)
+        .with_skip_rows(opts.skip_rows)
+        .with_infer_schema_length(infer_schema_length(opts))
+        .with_schema_overwrite(dtype_overrides(opts))
+        .map_parse_options(|parse| csv_parse_options(opts, parse, separator))
+}
+
+fn csv_parse_options(opts: &CsvOptions, parse: CsvParseOptions, separator: u8) -> CsvParseOptions {
+    let null_values = (!opts.null_values.is_empty()).then(|| {
+        NullValues::AllColumns(opts.null_values.iter().map(|v| v.as_str().into()).collect())
+    });
+    parse
+        .with_separator(separator)
+        .with_quote_char(Some(opts.quote_char.unwrap_or(b'"')))
+        .with_encoding(match opts.encoding {
+            Encoding::Utf8Lossy => CsvEncoding::LossyUtf8,
+            _ => CsvEncoding::Utf8,
+        })
+        .with_null_values(null_values)
+        .with_comment_prefix(opts.comment_prefix.as_deref())
+}
+
+/// `--infer-schema-length 0` means "read every row", which polars spells `None`.
+fn infer_schema_length(opts: &CsvOptions) -> Option<usize> {
+    match opts.infer_schema_length {
+        Some(0) => None,
+        Some(n) => Some(n),
+        None => Some(DEFAULT_INFER_SCHEMA_LENGTH),
+    }
+}
+
+fn dtype_overrides(opts: &CsvOptions) -> Option<SchemaRef> {
+    if opts.dtypes.is_empty() {
+        return None;
+    }
+    let schema = Schema::from_iter(
+        opts.dtypes
+            .iter()
+            .map(|(name, dtype)| Field::new(name.as_str().into(), dtype.clone())),
+    );
+    Some(Arc::new(schema))
+}
+
+/// Every Latin-1 byte is the Unicode code point of the same value.
+fn latin1_to_utf8(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .map(|&b| b as char)
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// Guess the format of a buffer from its magic bytes, returning the extension
+/// such a file would normally have. Anything unrecognised is treated as CSV.
+fn sniff_format(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(PARQUET_MAGIC) {
+        return "parquet";
+    }
+    if bytes.starts_with(ARROW_MAGIC) || bytes.starts_with(ARROW_STREAM_MAGIC) {
+        return "arrow";
+    }
+    match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'[') => "json",
+        Some(b'{') => "jsonl",
+        _ => "csv",
+    }
+}
+
+/// Pick the candidate delimiter that occurs most often (outside quotes) in the
+/// header line, defaulting to a comma.
+fn detect_delimiter(bytes: &[u8]) -> u8 {
+    let header = bytes.split(|&b| b == b'\n').next().unwrap_or(&[]);
+    let mut counts = [0usize; CSV_DELIMITERS.len()];
+    let mut in_quotes = false;
+    for &b in header {
+        if b == b'"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes {
+            if let Some(i) = CSV_DELIMITERS.iter().position(|&d| d == b) {
+                counts[i] += 1;
+            }
+        }
+    }
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, &n)| n > 0)
+        .max_by_key(|(i, &n)| (n, std::cmp::Reverse(*i)))
+        .map_or(b',', |(i, _)| CSV_DELIMITERS[i])
+}
+
+/// The `--delimiter` if given, otherwise the one detected from the file's header line.
+fn file_delimiter(file_path: &str, opts: &CsvOptions) -> std::io::Result<u8> {
+    if let Some(delimiter) = opts.delimiter {
+        return Ok(delimiter);
+    }
+    let mut header = Vec::new();
+    BufReader::new(std::fs::File::open(file_path)?)
+        .take(HEADER_SNIFF_BYTES)
+        .read_until(b'\n', &mut header)?;
+    Ok(detect_delimiter(&header))
+}
+
+/// Build a lazy scan over the file without reading any rows.
+/// Filters and sorts applied to the returned frame are pushed down into the scan.
+pub fn scan_file(
+    file_path: &str,
+    opts: &CsvOptions,
+) -> Result<LazyFrame, Box<dyn std::error::Error>> {
+    if Path::new(file_path).is_dir() {
+        return Ok(scan_hive_dataset(file_path)?);
+    }
+    // Compressed streams can't be scanned; decompress them into memory instead.
+    if compression(file_path).is_some() {
+        return Ok(load_dataframe(file_path, opts)?.lazy());
+    }
+    let lf = match extension(file_path) {
+        "csv" if opts.encoding == Encoding::Latin1 => load_dataframe(file_path, opts)?.lazy(),
+        "csv" => {
+            let delimiter = file_delimiter(file_path, opts)?;
+            LazyCsvReader::new(file_path)
+                .with_has_header(!opts.no_header)
+                .with_skip_rows(opts.skip_rows)
+                .with_infer_schema_length(infer_schema_length(opts))
+                .with_dtype_overwrite(dtype_overrides(opts))
+                .map_parse_options(|parse| csv_parse_options(opts, parse, delimiter))
+                .finish()?
+        }
+        "parquet" => LazyFrame::scan_parquet(file_path, ScanArgsParquet::default())?,
+        // A JSON array has to be parsed as a whole, so it can't be scanned.
+        "json" => read_json(file_path, JsonFormat::Json)?.lazy(),
+        "jsonl" | "ndjson" => LazyJsonLineReader::new(file_path).finish()?,
+        "arrow" | "feather" | "ipc" if is_ipc_file(file_path)? => {
+            LazyFrame::scan_ipc(file_path, ScanArgsIpc::default())?
+        }
+        // The stream format has no footer to seek to, so it is read in full.
+        "arrow" | "feather" | "ipc" => read_ipc(file_path)?.lazy(),
+        "" => load_bytes(std::fs::read(file_path)?, opts)?.lazy(),
+        ext => return Err(format!("Unsupported file format: .{}", ext).into()),
+    };
+    Ok(flatten_structs(lf)?)
+}
+
+/// Scan a directory of Parquet files laid out as `key=value/` folders. The keys
+/// become columns, and filters on them skip non-matching folders entirely.
+fn scan_hive_dataset(dir: &str) -> PolarsResult<LazyFrame> {
+    let args = ScanArgsParquet {
+        hive_options: polars::io::HiveOptions {
+            enabled: Some(true),
+            try_parse_dates: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    LazyFrame::scan_parquet(dir, args)
+}
+
+/// Whether any of the paths is a directory, which is always opened lazily so
+/// that filters can prune partitions.
+pub fn has_directory(paths: &[String]) -> bool {
+    paths.iter().any(|p| Path::new(p).is_dir())
+}
+
+fn read_json(file_path: &str, format: JsonFormat) -> PolarsResult<DataFrame> {
+    JsonReader::new(std::fs::File::open(file_path)?)
+        .with_json_format(format)
+        .finish()
+}
+
+/// Arrow IPC comes in two flavours: the file format, which starts with the
+/// `ARROW1` magic bytes, and the stream format, which has no magic at all.
+fn is_ipc_file(file_path: &str) -> std::io::Result<bool> {
+    let mut magic = [0u8; 6];
+    let mut file = std::fs::File::open(file_path)?;
+    Ok(file.read_exact(&mut magic).is_ok() && magic == ARROW_MAGIC)
+}
+
+fn read_ipc(file_path: &str) -> PolarsResult<DataFrame> {
+    let file = std::fs::File::open(file_path)?;
+    if is_ipc_file(file_path)? {
+        IpcReader::new(file)
+            .memory_mapped(Some(file_path.into()))
+            .finish()
+    } else {
+        IpcStreamReader::new(file).finish()
+    }
+}
+
+/// Replace every struct column with one column per field, named `parent.field`,
+/// until no struct columns are left. Lists are kept as-is and rendered inline.
+pub fn flatten_structs(mut lf: LazyFrame) -> PolarsResult<LazyFrame> {
+    loop {
+        let schema = lf.collect_schema()?;
+        if !schema
+            .iter_values()
+            .any(|dt| matches!(dt, DataType::Struct(_)))
+        {
+            return Ok(lf);
+        }
+        let exprs: Vec<Expr> = schema
+            .iter()
+            .flat_map(|(name, dtype)| match dtype {
+                DataType::Struct(fields) => fields
+                    .iter()
+                    .map(|f| {
+                        col(name.clone())
+                            .struct_()
+                            .field_by_name(f.name())
+                            .alias(format!("{}.{}", name, f.name()))
+                    })
+                    .collect(),
+                _ => vec![col(name.clone())],
+            })
+            .collect();
+        lf = lf.select(exprs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{App, Filter};
+
+    fn write_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_scan_csv_matches_eager_load() {
+        let path = write_file("iron_sight_scan.csv", "name,age\nAlice,30\nBob,25\n");
+        let eager = load_dataframe(&path, &CsvOptions::default()).unwrap();
+        let lazy = scan_file(&path, &CsvOptions::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert!(eager.equals(&lazy));
+    }
+
+    #[test]
+    fn test_load_ndjson_flattens_structs() {
+        let path = write_file(
+            "iron_sight_nested.ndjson",
+            "{\"id\":1,\"user\":{\"name\":\"Alice\",\"geo\":{\"city\":\"Rome\"}},\"tags\":[\"a\",\"b\"]}\n\
+             {\"id\":2,\"user\":{\"name\":\"Bob\",\"geo\":{\"city\":\"Oslo\"}},\"tags\":[]}\n",
+        );
+        let df = load_dataframe(&path, &CsvOptions::default()).unwrap();
+        let names: Vec<&str> = df.get_column_names().iter().map(|s| s.as_str()).collect();
+        assert_eq!(names, vec!["id", "user.name", "user.geo.city", "tags"]);
+        assert_eq!(df.height(), 2);
+        let lazy = scan_file(&path, &CsvOptions::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert!(df.equals(&lazy));
+    }
+
+    #[test]
+    fn test_load_json_array() {
+        let path = write_file(
+            "iron_sight_array.json",
+            "[{\"name\":\"Alice\",\"age\":30},{\"name\":\"Bob\",\"age\":25}]",
+        );
+        let df = load_dataframe(&path, &CsvOptions::default()).unwrap();
+        assert_eq!(df.shape(), (2, 2));
+    }
+
+    fn sample_df() -> DataFrame {
+        df! {
+            "name" => ["Alice", "Bob"],
+            "age"  => [30i64, 25],
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_load_arrow_ipc_file() {
+        let path = std::env::temp_dir().join("iron_sight_data.feather");
+        let mut df = sample_df();
+        IpcWriter::new(std::fs::File::create(&path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        let path = path.to_string_lossy();
+        assert!(load_dataframe(&path, &CsvOptions::default())
+            .unwrap()
+            .equals(&df));
+        assert!(scan_file(&path, &CsvOptions::default())
+            .unwrap()
+            .collect()
+            .unwrap()
+            .equals(&df));
+    }
+
+    #[test]
+    fn test_load_arrow_ipc_stream() {
+        let path = std::env::temp_dir().join("iron_sight_stream.arrow");
+        let mut df = sample_df();
+        IpcStreamWriter::new(std::fs::File::create(&path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        let path = path.to_string_lossy();
+        assert!(load_dataframe(&path, &CsvOptions::default())
+            .unwrap()
+            .equals(&df));
+        assert!(scan_file(&path, &CsvOptions::default())
+            .unwrap()
+            .collect()
+            .unwrap()
+            .equals(&df));
+    }
+
+    #[test]
+    fn test_sniff_format() {
+        assert_eq!(sniff_format(b"PAR1\x15\x04"), "parquet");
+        assert_eq!(sniff_format(b"ARROW1\0\0"), "arrow");
+        assert_eq!(sniff_format(&[0xFF, 0xFF, 0xFF, 0xFF, 0x10]), "arrow");
+        assert_eq!(sniff_format(b"  \n[{\"a\":1}]"), "json");
+        assert_eq!(sniff_format(b"{\"a\":1}\n{\"a\":2}"), "jsonl");
+        assert_eq!(sniff_format(b"a,b\n1,2"), "csv");
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter(b"a,b,c\n1,2,3"), b',');
+        assert_eq!(detect_delimiter(b"a;b;c\n1;2;3"), b';');
+        assert_eq!(detect_delimiter(b"a\tb\n1\t2"), b'\t');
+        assert_eq!(detect_delimiter(b"\"x,y\"|z\n1|2"), b'|');
+        assert_eq!(detect_delimiter(b"single\n1"), b',');
+    }
+
+    #[test]
+    fn test_load_bytes_semicolon_csv() {
+        let df = load_bytes(
+            b"name;age\nAlice;30\nBob;25\n".to_vec(),
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(df.shape(), (2, 2));
+        assert_eq!(df.get_column_names()[1].as_str(), "age");
+    }
+
+    #[test]
+    fn test_every_csv_detects_delimiter() {
+        use std::io::Write;
+        let csv = b"name;age\nAlice;30\nBob;25\n";
+        let plain = write_file(
+            "iron_sight_semicolon.csv",
+            std::str::from_utf8(csv).unwrap(),
+        );
+        let gz = std::env::temp_dir().join("iron_sight_semicolon.csv.gz");
+        let mut enc =
+            flate2::write::GzEncoder::new(std::fs::File::create(&gz).unwrap(), Default::default());
+        enc.write_all(csv).unwrap();
+        enc.finish().unwrap();
+        let latin1 = CsvOptions {
+            encoding: Encoding::Latin1,
+            ..Default::default()
+        };
+
+        for df in [
+            load_dataframe(&plain, &CsvOptions::default()).unwrap(),
+            scan_file(&plain, &CsvOptions::default())
+                .unwrap()
+                .collect()
+                .unwrap(),
+            load_dataframe(&gz.to_string_lossy(), &CsvOptions::default()).unwrap(),
+            load_dataframe(&plain, &latin1).unwrap(),
+        ] {
+            assert_eq!(df.shape(), (2, 2));
+        }
+
+        let comma = CsvOptions {
+            delimiter: Some(b','),
+            ..Default::default()
+        };
+        assert_eq!(load_dataframe(&plain, &comma).unwrap().width(), 1);
+    }
+
+    #[test]
+    fn test_load_bytes_parquet() {
+        let mut df = sample_df();
+        let mut buf = Vec::new();
+        ParquetWriter::new(&mut buf).finish(&mut df).unwrap();
+        assert!(load_bytes(buf, &CsvOptions::default()).unwrap().equals(&df));
+    }
+
+    #[test]
+    fn test_compound_extensions() {
+        assert_eq!(extension("data.csv.gz"), "csv");
+        assert_eq!(compression("data.csv.gz"), Some("gz"));
+        assert_eq!(extension("logs.2026.jsonl.zst"), "jsonl");
+        assert_eq!(extension("data.bz2"), "");
+        assert_eq!(extension("data.csv"), "csv");
+        assert_eq!(compression("data.csv"), None);
+    }
+
+    #[test]
+    fn test_load_compressed_csv() {
+        use std::io::Write;
+        let csv = b"name,age\nAlice,30\nBob,25\n";
+
+        let gz = std::env::temp_dir().join("iron_sight_data.csv.gz");
+        let mut enc =
+            flate2::write::GzEncoder::new(std::fs::File::create(&gz).unwrap(), Default::default());
+        enc.write_all(csv).unwrap();
+        enc.finish().unwrap();
+
+        let zst = std::env::temp_dir().join("iron_sight_data.csv.zst");
+        std::fs::write(&zst, zstd::encode_all(&csv[..], 0).unwrap()).unwrap();
+
+        let bz2 = std::env::temp_dir().join("iron_sight_data.csv.bz2");
+        let mut enc =
+            bzip2::write::BzEncoder::new(std::fs::File::create(&bz2).unwrap(), Default::default());
+        enc.write_all(csv).unwrap();
+        enc.finish().unwrap();
+
+        for path in [gz, zst, bz2] {
+            let df = load_dataframe(&path.to_string_lossy(), &CsvOptions::default()).unwrap();
+            assert_eq!(df.shape(), (2, 2), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_csv_reader_options() {
+        let path = write_file(
+            "iron_sight_options.csv",
+            "# exported 2026-10-01\nAlice;NA;00123\nBob;25;04567\n",
+        );
+        let opts = CsvOptions {
+            delimiter: Some(b';'),
+            no_header: true,
+            null_values: vec!["NA".to_string()],
+            comment_prefix: Some("#".to_string()),
+            dtypes: vec![("column_3".to_string(), DataType::String)],
+            ..Default::default()
+        };
+        for df in [
+            load_dataframe(&path, &opts).unwrap(),
+            scan_file(&path, &opts).unwrap().collect().unwrap(),
+        ] {
+            assert_eq!(df.shape(), (2, 3));
+            assert_eq!(df.column("column_2").unwrap().null_count(), 1);
+            let zips = df.column("column_3").unwrap();
+            assert_eq!(zips.str().unwrap().get(0), Some("00123"));
+        }
+    }
+
+    #[test]
+    fn test_latin1_encoding() {
+        let path = std::env::temp_dir().join("iron_sight_latin1.csv");
+        std::fs::write(&path, b"city\nM\xfcnchen\n").unwrap();
+        let opts = CsvOptions {
+            encoding: Encoding::Latin1,
+            ..Default::default()
+        };
+        let df = load_dataframe(&path.to_string_lossy(), &opts).unwrap();
+        assert_eq!(
+            df.column("city").unwrap().str().unwrap().get(0),
+            Some("München")
+        );
+    }
+
+    #[test]
+    fn test_load_files_unifies_schemas() {
+        let a = write_file("iron_sight_part_a.csv", "id,amount\n1,10\n2,20\n");
+        let b = write_file("iron_sight_part_b.csv", "id,amount,note\n3,1.5,late\n");
+        let paths = vec![a.clone(), b.clone()];
+        let opts = CsvOptions::default();
+        for df in [
+            load_files(&paths, &opts, true).unwrap(),
+            scan_files(&paths, &opts, true).unwrap().collect().unwrap(),
+        ] {
+            let names: Vec<&str> = df.get_column_names().iter().map(|s| s.as_str()).collect();
+            assert_eq!(names, vec![SOURCE_FILE_COLUMN, "id", "amount", "note"]);
+            assert_eq!(df.height(), 3);
+            assert_eq!(df.column("amount").unwrap().dtype(), &DataType::Float64);
+            assert_eq!(df.column("note").unwrap().null_count(), 2);
+            let sources = df.column(SOURCE_FILE_COLUMN).unwrap();
+            assert_eq!(sources.str().unwrap().get(2), Some(b.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_expand_paths() {
+        let dir = std::env::temp_dir().join("iron_sight_glob");
+        std::fs::create_dir_all(&dir).unwrap();
+        for day in ["02", "01"] {
+            std::fs::write(dir.join(format!("events_2026-10-{}.csv", day)), "a\n1\n").unwrap();
+        }
+        let pattern = dir
+            .join("events_2026-10-*.csv")
+            .to_string_lossy()
+            .into_owned();
+        let paths = expand_paths(&[pattern]).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("events_2026-10-01.csv"));
+        assert_eq!(
+            expand_paths(&["plain.csv".to_string()]).unwrap(),
+            vec!["plain.csv"]
+        );
+        let missing = dir.join("nothing_*.csv").to_string_lossy().into_owned();
+        assert!(expand_paths(&[missing]).is_err());
+    }
+
+    #[test]
+    fn test_hive_partitioned_directory() {
+        let root = std::env::temp_dir().join("iron_sight_hive");
+        let _ = std::fs::remove_dir_all(&root);
+        for (year, month, amount) in [(2025, 12, 1i64), (2026, 9, 2), (2026, 10, 3)] {
+            let dir = root.join(format!("year={}/month={}", year, month));
+            std::fs::create_dir_all(&dir).unwrap();
+            let mut df = df! { "amount" => [amount, amount * 10] }.unwrap();
+            ParquetWriter::new(std::fs::File::create(dir.join("part-0.parquet")).unwrap())
+                .finish(&mut df)
+                .unwrap();
+        }
+        let root = root.to_string_lossy().into_owned();
+        assert!(has_directory(std::slice::from_ref(&root)));
+
+        let df = load_dataframe(&root, &CsvOptions::default()).unwrap();
+        let names: Vec<&str> = df.get_column_names().iter().map(|s| s.as_str()).collect();
+        assert_eq!(names, vec!["amount", "year", "month"]);
+        assert_eq!(df.height(), 6);
+
+        let filtered = scan_file(&root, &CsvOptions::default())
+            .unwrap()
+            .filter(col("year").eq(lit(2026)).and(col("month").gt_eq(lit(10))))
+            .collect()
+            .unwrap();
+        assert_eq!(filtered.height(), 2);
+    }
+
+    #[test]
+    fn test_filter_prunes_hive_partitions() {
+        let root = std::env::temp_dir().join("iron_sight_hive_pruning");
+        let _ = std::fs::remove_dir_all(&root);
+        for year in [2025, 2026] {
+            let dir = root.join(format!("year={}", year));
+            std::fs::create_dir_all(&dir).unwrap();
+            let mut df = df! { "amount" => [1i64, 2] }.unwrap();
+            ParquetWriter::new(std::fs::File::create(dir.join("part-0.parquet")).unwrap())
+                .finish(&mut df)
+                .unwrap();
+        }
+        let root_path = root.to_string_lossy().into_owned();
+        let lf = scan_file(&root_path, &CsvOptions::default()).unwrap();
+        let mut app = App::new_lazy(lf, root_path).unwrap();
+        // Only a pruned partition can be unreadable without failing the scan.
+        std::fs::write(root.join("year=2026/part-0.parquet"), b"not parquet").unwrap();
+        app.filters.push(Filter::new(1, "= 2025"));
+        app.update_filter();
+        assert_eq!(app.filter_error, None);
+        assert_eq!(app.row_count(), 2);
+        assert_eq!(app.collect_view().unwrap().height(), 2);
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        assert!(load_dataframe("data.xlsx", &CsvOptions::default()).is_err());
+        assert!(scan_file("data.xlsx", &CsvOptions::default()).is_err());
+    }
+}
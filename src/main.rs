@@ -1,38 +1,61 @@
 mod app;
+mod cli;
+mod compute;
+mod diff;
 mod events;
+mod export;
+mod filter;
+mod headless;
+mod loader;
 mod ui;
 
-use app::{App, Config};
+use app::App;
+use cli::{Command, Config};
 use events::run_app;
-use polars::prelude::*;
-use std::{env, path::Path};
-
-fn load_dataframe(file_path: &str) -> Result<DataFrame, Box<dyn std::error::Error>> {
-    let ext = Path::new(file_path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
-
-    match ext {
-        "csv" => Ok(CsvReadOptions::default()
-            .try_into_reader_with_file_path(Some(file_path.into()))?
-            .finish()?),
-        "parquet" => Ok(ParquetReader::new(std::fs::File::open(file_path)?).finish()?),
-        _ => Err(format!("Unsupported file format: .{}", ext).into()),
-    }
-}
+use loader::{expand_paths, has_directory, load_files, load_stdin, scan_files};
+use std::env;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::new(env::args()).unwrap_or_else(|err| {
-        eprintln!("Problem parsing arguments: {}", err);
-        std::process::exit(1);
-    });
+    let config = Config::new(env::args()).unwrap_or_else(|err| err.exit());
+    let display_name = config.paths.join(" ");
 
-    let df = load_dataframe(&config.file_path).unwrap_or_else(|err| {
+    // Stdin is fully consumed before the TUI starts; crossterm then reads keys
+    // from /dev/tty because stdin is no longer a terminal. A pipe can't be
+    // scanned, so `--lazy` has no effect there.
+    let mut app = if let Some(Command::Diff(opts)) = &config.command {
+        diff::open_diff(opts)
+    } else if config.reads_stdin() {
+        load_stdin(&config.csv).map(|df| App::new(df, "<stdin>".to_string()))
+    } else {
+        expand_paths(&config.paths).and_then(|paths| {
+            let mut app = if config.lazy || has_directory(&paths) {
+                let lf = scan_files(&paths, &config.csv, config.source_column)?;
+                App::new_lazy(lf, display_name)?
+            } else {
+                let df = load_files(&paths, &config.csv, config.source_column)?;
+                App::new(df, display_name)
+            };
+            app.file_count = paths.len();
+            Ok(app)
+        })
+    }
+    .unwrap_or_else(|err| {
         eprintln!("Problem loading file: {}", err);
         std::process::exit(1);
     });
 
-    let app = App::new(df, config.file_path);
+    app.frozen_columns = config.freeze;
+    if config.command.is_none() {
+        app.csv_options = config.csv.clone();
+    }
+
+    if config.query.is_headless() {
+        if let Err(err) = headless::run(app, &config.query) {
+            eprintln!("Query failed: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     ratatui::run(|terminal| run_app(terminal, app))
 }
@@ -1,4 +1,7 @@
-use crate::app::{AggFunc, App, ColumnProfile, Mode, PlotType};
+use crate::app::{
+    to_display_strings, AggFunc, App, ColumnProfile, Mode, PlotType, StatusMessage, PIVOT_FIELDS,
+};
+use crate::diff::{DiffSummary, ADDED, DIFF_CHANGED_COLUMN, DIFF_STATUS_COLUMN, REMOVED};
 use catppuccin::PALETTE;
 use polars::prelude::{DataType, Series};
 use ratatui::layout::{Constraint, Layout, Position, Rect};
@@ -6,7 +9,8 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::symbols;
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
-    Axis, Block, BorderType, Borders, Cell, Chart, Clear, Dataset, GraphType, Paragraph, Row, Table,
+    Axis, Block, BorderType, Borders, Cell, Chart, Clear, Dataset, GraphType, Paragraph, Row,
+    Table, TableState,
 };
 use ratatui::Frame;
 
@@ -37,7 +41,7 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
 
     // 2 borders + 1 header row + 1 header bottom-margin = 4 rows of overhead.
     let page_h = (chunks[0].height.saturating_sub(4)) as usize;
-    let total_rows = app.view.height();
+    let total_rows = app.row_count();
     let selected = app.state.selected().unwrap_or(0);
 
     // Scroll the viewport to keep `selected` visible.
@@ -52,23 +56,53 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         .min(total_rows.saturating_sub(page_h.max(1)));
 
     let slice_len = page_h.min(total_rows.saturating_sub(app.view_offset));
-    let visible_view = app.view.slice(app.view_offset as i64, slice_len);
-
-    let header_cells = Row::new((0..app.headers.len()).map(|i| {
-        Cell::from(app.header_label(i)).style(
-            Style::default()
-                .fg(c(m.lavender))
-                .add_modifier(Modifier::BOLD),
-        )
+    let visible_view = app.visible_rows(app.view_offset, slice_len);
+
+    // Hidden columns stay in the view for filters but aren't rendered, and
+    // only the columns that fit beside the frozen ones are.
+    app.ensure_column_shown();
+    let (fitted, frozen) = app.fit_columns(chunks[0].width.saturating_sub(2));
+    let shown: Vec<usize> = fitted.iter().map(|&(i, _)| i).collect();
+
+    let header_cells = Row::new(shown.iter().enumerate().map(|(pos, &i)| {
+        let fg = if pos < frozen { m.peach } else { m.lavender };
+        Cell::from(app.header_label(i))
+            .style(Style::default().fg(c(fg)).add_modifier(Modifier::BOLD))
     }))
     .style(Style::default().bg(c(m.surface0)));
 
-    let str_columns: Vec<Option<Series>> = visible_view
-        .get_columns()
+    let str_columns: Vec<Option<Series>> = shown
         .iter()
-        .map(|col| col.as_series().and_then(|s| s.cast(&DataType::String).ok()))
+        .map(|&i| {
+            visible_view
+                .get_columns()
+                .get(i)
+                .and_then(|col| col.as_series())
+                .and_then(to_display_strings)
+        })
         .collect();
 
+    // In a diff, rows are coloured by what happened to them and changed cells
+    // stand out.
+    let diff_status_col = app.diff.as_ref().and_then(|_| {
+        shown
+            .iter()
+            .position(|&i| app.headers[i] == DIFF_STATUS_COLUMN)
+    });
+    let changed_cols = app.diff.as_ref().and_then(|_| {
+        visible_view
+            .column(DIFF_CHANGED_COLUMN)
+            .ok()
+            .and_then(|c| c.list().ok().cloned())
+    });
+    let cell_text = |s: &Option<Series>, i: usize| {
+        s.as_ref()
+            .and_then(|series| series.str().ok())
+            .and_then(|ca| ca.get(i))
+            .unwrap_or("")
+            .to_string()
+    };
+
     let rows: Vec<Row> = (0..slice_len)
         .map(|i| {
             let abs_row = app.view_offset + i;
@@ -77,35 +111,50 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
             } else {
                 c(m.mantle)
             };
+            let status = diff_status_col.map(|idx| cell_text(&str_columns[idx], i));
+            let fg = match status.as_deref() {
+                Some(ADDED) => c(m.green),
+                Some(REMOVED) => c(m.red),
+                _ => c(m.text),
+            };
+            let changed = changed_cols
+                .as_ref()
+                .and_then(|ca| ca.get_as_series(i))
+                .unwrap_or_default();
+            let is_changed = |name: &str| {
+                changed
+                    .str()
+                    .is_ok_and(|names| names.into_iter().any(|n| n == Some(name)))
+            };
             Row::new(
                 str_columns
                     .iter()
-                    .map(|s| {
-                        Cell::from(
-                            s.as_ref()
-                                .and_then(|series| series.str().ok())
-                                .and_then(|ca| ca.get(i))
-                                .unwrap_or("")
-                                .to_string(),
-                        )
+                    .zip(&shown)
+                    .map(|(s, &col_idx)| {
+                        let text = cell_text(s, i);
+                        if is_changed(&app.headers[col_idx]) {
+                            Cell::from(text).style(
+                                Style::default()
+                                    .fg(c(m.yellow))
+                                    .add_modifier(Modifier::BOLD),
+                            )
+                        } else {
+                            Cell::from(text)
+                        }
                     })
                     .collect::<Vec<Cell>>(),
             )
-            .style(Style::default().bg(bg).fg(c(m.text)))
+            .style(Style::default().bg(bg).fg(fg))
         })
         .collect();
 
-    let widths: Vec<Constraint> = app
-        .column_widths
-        .iter()
-        .map(|w| Constraint::Length(*w))
-        .collect();
+    let widths: Vec<Constraint> = fitted.iter().map(|&(_, w)| Constraint::Length(w)).collect();
 
     let table = Table::new(rows, widths)
         .header(header_cells.bottom_margin(1))
         .block(
             Block::default()
-                .title(format!(" {} ", app.file_path))
+                .title(format!(" {} ", app.source_label()))
                 .title_style(Style::default().fg(c(m.blue)).add_modifier(Modifier::BOLD))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
@@ -127,7 +176,11 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
     // Render with a temporary state so ratatui doesn't try to manage scroll offset.
     let mut render_state = ratatui::widgets::TableState::default();
     render_state.select(Some(selected.saturating_sub(app.view_offset)));
-    render_state.select_column(app.state.selected_column());
+    render_state.select_column(
+        app.state
+            .selected_column()
+            .and_then(|col| shown.iter().position(|&i| i == col)),
+    );
     frame.render_stateful_widget(table, chunks[0], &mut render_state);
     frame.render_widget(bar, chunks[1]);
 
@@ -161,6 +214,28 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         frame.render_widget(popup, area);
     }
 
+    if app.show_diff_summary {
+        if let Some(summary) = &app.diff {
+            let area = centered_rect(50, 60, frame.area());
+            frame.render_widget(Clear, area);
+            let popup = Paragraph::new(diff_summary_text(summary, m))
+                .block(
+                    Block::default()
+                        .title(" Diff summary — D or Esc to close ")
+                        .title_style(
+                            Style::default()
+                                .fg(c(m.lavender))
+                                .add_modifier(Modifier::BOLD),
+                        )
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(c(m.lavender))),
+                )
+                .style(Style::default().bg(c(m.surface0)).fg(c(m.text)));
+            frame.render_widget(popup, area);
+        }
+    }
+
     if app.show_help {
         let area = centered_rect(55, 80, frame.area());
         frame.render_widget(Clear, area);
@@ -184,6 +259,22 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
     if matches!(app.mode, Mode::UniqueValues) {
         render_unique_values_popup(frame, app, m);
     }
+
+    if matches!(app.mode, Mode::FilterList) {
+        render_filter_list_popup(frame, app, m);
+    }
+
+    if matches!(app.mode, Mode::ColumnChooser) {
+        render_column_chooser_popup(frame, app, m);
+    }
+
+    if matches!(app.mode, Mode::PivotSetup) {
+        render_pivot_popup(frame, app, m);
+    }
+
+    if matches!(app.mode, Mode::JoinSetup) {
+        render_join_popup(frame, app, m);
+    }
 }
 
 fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
@@ -238,15 +329,178 @@ fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
                 .fg(c(m.base))
                 .add_modifier(Modifier::BOLD),
         ),
-        Mode::Filter => (
-            format!(" f {}_ (>,<,>=,<=,!=,= for numbers) ", app.filter_input),
+        Mode::Filter => match &app.filter_error {
+            Some(err) => (
+                format!(" f {}_  ✗ {} ", app.filter_input, err),
+                Style::default()
+                    .bg(c(m.red))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            None => (
+                format!(
+                    " f {}_ (> < = != ~ in between is null starts with, and/or/not) ",
+                    app.filter_input
+                ),
+                Style::default()
+                    .bg(c(m.sapphire))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        },
+        Mode::Export => (
+            format!(
+                " w {}_  (.csv .parquet .json .jsonl .arrow)  Enter write  Esc cancel ",
+                app.export_input
+            ),
+            Style::default()
+                .bg(c(m.peach))
+                .fg(c(m.base))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Mode::ConfirmOverwrite => (
+            format!(
+                " {} already exists — overwrite? (y/n) ",
+                app.export_input.trim()
+            ),
+            Style::default()
+                .bg(c(m.red))
+                .fg(c(m.base))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Mode::FilterList => match (&app.filter_edit, &app.filter_error) {
+            (Some(query), Some(err)) => (
+                format!(" Edit: {}_  ✗ {} ", query, err),
+                Style::default()
+                    .bg(c(m.red))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            (Some(query), None) => (
+                format!(" Edit: {}_  |  Enter apply  |  Esc cancel ", query),
+                Style::default()
+                    .bg(c(m.sapphire))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            (None, _) => (
+                " Filters  |  Space enable/disable  |  ! invert  |  e edit  |  d delete  |  Esc close "
+                    .to_string(),
+                Style::default()
+                    .bg(c(m.sapphire))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        },
+        Mode::ColumnChooser => match &app.status_message {
+            Some(StatusMessage::Error(err)) => (
+                format!(" ✗ {} ", err),
+                Style::default()
+                    .bg(c(m.red))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            _ => (
+                " Columns  |  Space show/hide  |  h/l move  |  p pin  |  r reset  |  Enter jump  |  Esc close "
+                    .to_string(),
+                Style::default()
+                    .bg(c(m.green))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        },
+        Mode::PivotSetup => (
+            " Pivot  |  j/k field  |  h/l change  |  Enter build  |  Esc cancel ".to_string(),
+            Style::default()
+                .bg(c(m.pink))
+                .fg(c(m.base))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Mode::Compute => match &app.compute_error {
+            Some(err) => (
+                format!(" c {}_  ✗ {} ", app.compute_input, err),
+                Style::default()
+                    .bg(c(m.red))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            None => (
+                format!(
+                    " c {}_  (name = expression, e.g. margin = (revenue - cost) / revenue) ",
+                    app.compute_input
+                ),
+                Style::default()
+                    .bg(c(m.maroon))
+                    .fg(c(m.base))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        },
+        Mode::Join => (
+            format!(
+                " J {}_  (any supported file)  Enter load  empty Enter drops joins  Esc cancel ",
+                app.join_input
+            ),
+            Style::default()
+                .bg(c(m.flamingo))
+                .fg(c(m.base))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Mode::JoinSetup => (
+            format!(
+                " Join {}  |  j/k field  |  h/l change  |  +/- key  |  Enter join  |  Esc cancel ",
+                app.join_setup.path
+            ),
+            Style::default()
+                .bg(c(m.flamingo))
+                .fg(c(m.base))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Mode::Sql => (
+            format!(
+                " Q {}_  (FROM self)  Enter run  empty Enter restores data  Esc cancel ",
+                app.sql_input
+            ),
             Style::default()
-                .bg(c(m.sapphire))
+                .bg(c(m.lavender))
                 .fg(c(m.base))
                 .add_modifier(Modifier::BOLD),
         ),
         Mode::Normal => {
-            let (text, fg) = if app.groupby_active {
+            let (text, fg) = if let Some(msg) = &app.status_message {
+                match msg {
+                    StatusMessage::Info(text) => (format!(" ✓ {} ", text), c(m.green)),
+                    StatusMessage::Error(text) => (format!(" ✗ {} ", text), c(m.red)),
+                }
+            } else if let (Some(diff), None, true) = (
+                &app.diff,
+                &app.pivot,
+                app.filters.is_empty() && !app.groupby_active,
+            ) {
+                (
+                    format!(
+                        " ◆ DIFF  +{} added  −{} removed  ~{} changed | Row {}/{} | D summary ",
+                        diff.added_rows,
+                        diff.removed_rows,
+                        diff.changed_rows,
+                        app.state.selected().map_or(0, |i| i + 1),
+                        app.row_count()
+                    ),
+                    c(m.yellow),
+                )
+            } else if let Some(pivot) = &app.pivot {
+                (
+                    format!(
+                        " ◆ PIVOT  Rows: {} | Columns: {} | Values: {}[{}]{} | {} rows | P clear ",
+                        app.saved_headers[pivot.rows],
+                        app.saved_headers[pivot.columns],
+                        app.saved_headers[pivot.values],
+                        pivot.func.symbol(),
+                        if pivot.totals { " | Totals" } else { "" },
+                        app.row_count()
+                    ),
+                    c(m.pink),
+                )
+            } else if app.groupby_active {
                 let key_names = app
                     .saved_headers
                     .iter()
@@ -255,29 +509,23 @@ fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
                     .map(|(_, h)| h.as_str())
                     .collect::<Vec<_>>()
                     .join(", ");
-                let mut agg_entries: Vec<(usize, &AggFunc)> =
+                let mut agg_entries: Vec<(usize, &Vec<AggFunc>)> =
                     app.groupby_aggs.iter().map(|(i, f)| (*i, f)).collect();
                 agg_entries.sort_by_key(|(i, _)| *i);
                 let agg_summary = agg_entries
                     .iter()
-                    .map(|(i, func)| {
-                        let sym = match func {
-                            AggFunc::Sum => "Σ",
-                            AggFunc::Mean => "μ",
-                            AggFunc::Count => "#",
-                            AggFunc::Min => "↓",
-                            AggFunc::Max => "↑",
-                        };
-                        format!("{}[{}]", app.saved_headers[*i], sym)
+                    .map(|(i, funcs)| {
+                        let syms: Vec<String> = funcs.iter().map(AggFunc::symbol).collect();
+                        format!("{}[{}]", app.saved_headers[*i], syms.join(" "))
                     })
                     .collect::<Vec<_>>()
                     .join(" ");
                 (
                     format!(
-                        " ◆ GROUPED  By: {} | Agg: {} | {} rows ",
+                        " ◆ GROUPED  By: {} | Agg: {} | {} rows | Enter drill down ",
                         key_names,
                         agg_summary,
-                        app.view.height()
+                        app.row_count()
                     ),
                     c(m.yellow),
                 )
@@ -292,6 +540,24 @@ fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
                     format!(" GroupBy: {} | press B to execute ", key_names),
                     c(m.peach),
                 )
+            } else if !app.drill_stack.is_empty() {
+                let breadcrumb = app
+                    .drill_stack
+                    .iter()
+                    .map(|(label, _)| label.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" › ");
+                (
+                    format!(
+                        " ◆ GROUPS › {} | Row {}/{} | Col {}/{} | Backspace back to groups ",
+                        breadcrumb,
+                        app.state.selected().map_or(0, |i| i + 1),
+                        app.row_count(),
+                        app.state.selected_column().map_or(0, |i| i + 1),
+                        app.headers.len()
+                    ),
+                    c(m.yellow),
+                )
             } else if !app.search_results.is_empty() {
                 (
                     format!(
@@ -302,12 +568,18 @@ fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
                     ),
                     c(m.sky),
                 )
-            } else if !app.filters.is_empty() {
+            } else if app.filters.iter().any(|f| f.enabled) {
                 let filter_summary = app
                     .filters
                     .iter()
-                    .map(|(col, q)| {
-                        format!("[{}: {}]", app.headers.get(*col).map_or("?", |h| h), q)
+                    .filter(|f| f.enabled)
+                    .map(|f| {
+                        format!(
+                            "[{}: {}{}]",
+                            app.headers.get(f.column).map_or("?", |h| h),
+                            if f.inverted { "not " } else { "" },
+                            f.query
+                        )
                     })
                     .collect::<Vec<_>>()
                     .join(" ");
@@ -316,22 +588,44 @@ fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
                         " {} | Row {}/{} | Col {}/{} | {} ",
                         filter_summary,
                         app.state.selected().map_or(0, |i| i + 1),
-                        app.view.height(),
+                        app.row_count(),
                         app.state.selected_column().map_or(0, |i| i + 1),
                         app.headers.len(),
-                        app.file_path
+                        app.source_label()
                     ),
                     c(m.teal),
                 )
+            } else if app.sql_view.is_none() && !app.joins.is_empty() {
+                (
+                    format!(
+                        " ◆ JOIN  {} | Row {}/{} | Col {}/{} | J, empty path restores ",
+                        app.joins.join(" → "),
+                        app.state.selected().map_or(0, |i| i + 1),
+                        app.row_count(),
+                        app.state.selected_column().map_or(0, |i| i + 1),
+                        app.headers.len()
+                    ),
+                    c(m.flamingo),
+                )
+            } else if app.sql_view.is_some() {
+                (
+                    format!(
+                        " ◆ SQL  {} | Row {}/{} | Q edit, empty query restores ",
+                        app.sql_query,
+                        app.state.selected().map_or(0, |i| i + 1),
+                        app.row_count()
+                    ),
+                    c(m.lavender),
+                )
             } else {
                 (
                     format!(
                         " Row {}/{} | Col {}/{} | {}  ? help ",
                         app.state.selected().map_or(0, |i| i + 1),
-                        app.view.height(),
+                        app.row_count(),
                         app.state.selected_column().map_or(0, |i| i + 1),
                         app.headers.len(),
-                        app.file_path
+                        app.source_label()
                     ),
                     c(m.subtext1),
                 )
@@ -341,6 +635,65 @@ fn get_bar(app: &App, m: &catppuccin::FlavorColors) -> (String, Style) {
     }
 }
 
+fn diff_summary_text<'a>(summary: &DiffSummary, m: &catppuccin::FlavorColors) -> Text<'a> {
+    let section = |title: &'a str| {
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(c(m.lavender))
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let item = |text: String, color: Color| {
+        Line::from(Span::styled(
+            format!("  {}", text),
+            Style::default().fg(color),
+        ))
+    };
+    let mut lines = vec![
+        section("Rows"),
+        item(format!("+{} added", summary.added_rows), c(m.green)),
+        item(format!("−{} removed", summary.removed_rows), c(m.red)),
+        item(format!("~{} changed", summary.changed_rows), c(m.yellow)),
+        Line::raw(""),
+        section("Schema"),
+    ];
+    let schema_len = lines.len();
+    lines.extend(
+        summary
+            .added_columns
+            .iter()
+            .map(|name| item(format!("+ {}", name), c(m.green))),
+    );
+    lines.extend(
+        summary
+            .removed_columns
+            .iter()
+            .map(|name| item(format!("− {}", name), c(m.red))),
+    );
+    lines.extend(
+        summary
+            .dtype_changes
+            .iter()
+            .map(|(name, old, new)| item(format!("~ {}: {} → {}", name, old, new), c(m.yellow))),
+    );
+    if lines.len() == schema_len {
+        lines.push(item("no differences".to_string(), c(m.subtext1)));
+    }
+    lines.push(Line::raw(""));
+    lines.push(section("Changed cells per column"));
+    if summary.column_changes.is_empty() {
+        lines.push(item("none".to_string(), c(m.subtext1)));
+    }
+    lines.extend(
+        summary
+            .column_changes
+            .iter()
+            .map(|(name, n)| item(format!("{:<20} {}", name, n), c(m.text))),
+    );
+    Text::from(lines)
+}
+
 fn help_text(m: &catppuccin::FlavorColors) -> Text<'static> {
     let section = |title: &'static str| {
         Line::from(vec![
@@ -381,16 +734,37 @@ fn help_text(m: &catppuccin::FlavorColors) -> Text<'static> {
         key("f", "Enter filter mode (current column)"),
         key("Enter", "Apply filter"),
         key("F", "Clear all filters"),
+        key("L", "Filter list: Space toggle, ! invert, e edit, d delete"),
         key("Esc", "Discard input"),
         key("", "  >, <, >=, <=, !=, = for numeric columns"),
         Line::raw(""),
         section("Sort"),
         key("s", "Sort by column (toggles asc / desc)"),
+        key("+ / -", "Add / remove column as a secondary sort key"),
+        key("z", "Toggle nulls first / last for the sort key"),
         Line::raw(""),
         section("Group By"),
         key("b", "Toggle group-by key [K]"),
-        key("a", "Cycle aggregation  [Σ μ # ↓ ↑]"),
+        key("a", "Cycle aggregation  [Σ μ x̃ # ↓ ↑ σ p90 …]"),
+        key("A", "Add another aggregation to the column"),
         key("B", "Execute / clear group-by"),
+        key("Enter", "Drill into the rows of the selected group"),
+        key("Backspace", "Back to the grouped view"),
+        Line::raw(""),
+        section("Pivot"),
+        key("P", "Set up a pivot table / clear it"),
+        key(
+            "j / k",
+            "Pick a field (rows, columns, values, aggregate, totals)",
+        ),
+        key("h / l", "Change the field"),
+        key("Enter", "Build the pivot table"),
+        Line::raw(""),
+        section("Join"),
+        key("J", "Join a file into the view (empty path drops joins)"),
+        key("h / l", "Change the view key, file key or join type"),
+        key("+ / -", "Add or remove a key pair"),
+        key("Enter", "Join"),
         Line::raw(""),
         section("Plot"),
         key("p", "Mark column as Y, enter pick-X mode"),
@@ -399,12 +773,33 @@ fn help_text(m: &catppuccin::FlavorColors) -> Text<'static> {
         key("t", "Toggle line / bar chart"),
         key("Esc / p", "Close chart"),
         Line::raw(""),
+        section("SQL"),
+        key("Q", "Query the data with SQL (FROM self)"),
+        key("Q Enter", "Empty query returns to the loaded data"),
+        Line::raw(""),
+        section("Export"),
+        key("w", "Write view to file (format from extension)"),
+        Line::raw(""),
         section("Other"),
         key("u", "Unique values popup (searchable, Enter to filter)"),
         key("i", "Column Inspector (schema + stats)"),
+        key("C", "Column chooser (show/hide, move, pin columns)"),
+        key(
+            "Z",
+            "Freeze columns up to the cursor while scrolling (again to unfreeze)",
+        ),
         key("_", "Autofit column width"),
         key("=", "Autofit all columns"),
+        key(
+            "U / Ctrl-r",
+            "Undo / redo filter, sort, group-by and column changes",
+        ),
+        key(
+            "c",
+            "Add a computed column, e.g. margin = (revenue - cost) / revenue",
+        ),
         key("S", "Toggle column stats popup"),
+        key("D", "Toggle the summary of a diff"),
         key("?", "Toggle this help"),
         key("q", "Quit"),
         Line::raw(""),
@@ -490,6 +885,249 @@ fn render_unique_values_popup(frame: &mut Frame, app: &mut App, m: &catppuccin::
     frame.render_stateful_widget(table, zones[1], &mut app.unique_values_state);
 }
 
+fn render_filter_list_popup(frame: &mut Frame, app: &mut App, m: &catppuccin::FlavorColors) {
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Filters ({} active, {} rows) ",
+            app.filters.iter().filter(|f| f.enabled).count(),
+            app.row_count()
+        ))
+        .title_style(
+            Style::default()
+                .fg(c(m.sapphire))
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(c(m.sapphire)))
+        .style(Style::default().bg(c(m.base)));
+
+    let header_style = Style::default()
+        .fg(c(m.lavender))
+        .add_modifier(Modifier::BOLD);
+    let header = Row::new([
+        Cell::from("On").style(header_style),
+        Cell::from("Column").style(header_style),
+        Cell::from("Query").style(header_style),
+        Cell::from("Rows").style(header_style),
+    ])
+    .style(Style::default().bg(c(m.surface0)))
+    .bottom_margin(1);
+
+    let rows: Vec<Row> = app
+        .filters
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let bg = if i % 2 == 0 { c(m.base) } else { c(m.mantle) };
+            let fg = if f.enabled { c(m.text) } else { c(m.overlay0) };
+            let impact = app.filter_impacts.get(i).copied().unwrap_or(0);
+            // Enabled filters remove rows; disabled ones would.
+            let impact = if f.enabled {
+                format!("−{}", impact)
+            } else {
+                format!("(−{})", impact)
+            };
+            Row::new([
+                Cell::from(if f.enabled { "✓" } else { "✗" }),
+                Cell::from(app.headers.get(f.column).cloned().unwrap_or_default()),
+                Cell::from(if f.inverted {
+                    format!("not ({})", f.query)
+                } else {
+                    f.query.clone()
+                }),
+                Cell::from(impact).style(Style::default().fg(c(m.subtext1))),
+            ])
+            .style(Style::default().bg(bg).fg(fg))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(16),
+            Constraint::Min(10),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(c(m.sapphire))
+            .fg(c(m.base))
+            .add_modifier(Modifier::BOLD),
+    );
+
+    frame.render_stateful_widget(table, area, &mut app.filter_list_state);
+}
+
+fn render_column_chooser_popup(frame: &mut Frame, app: &mut App, m: &catppuccin::FlavorColors) {
+    let area = centered_rect(40, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Columns ({} of {} shown) ",
+            app.display_columns().len(),
+            app.headers.len()
+        ))
+        .title_style(Style::default().fg(c(m.green)).add_modifier(Modifier::BOLD))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(c(m.green)))
+        .style(Style::default().bg(c(m.base)));
+
+    let header_style = Style::default()
+        .fg(c(m.lavender))
+        .add_modifier(Modifier::BOLD);
+    let header = Row::new([
+        Cell::from("Show").style(header_style),
+        Cell::from("Pin").style(header_style),
+        Cell::from("Column").style(header_style),
+    ])
+    .style(Style::default().bg(c(m.surface0)))
+    .bottom_margin(1);
+
+    let rows: Vec<Row> = app
+        .column_order()
+        .into_iter()
+        .enumerate()
+        .map(|(pos, i)| {
+            let bg = if pos % 2 == 0 { c(m.base) } else { c(m.mantle) };
+            let hidden = app.is_column_hidden(i);
+            let fg = if hidden { c(m.overlay0) } else { c(m.text) };
+            Row::new([
+                Cell::from(if hidden { "✗" } else { "✓" }),
+                Cell::from(if app.is_column_pinned(i) { "◆" } else { "" }),
+                Cell::from(app.headers[i].clone()),
+            ])
+            .style(Style::default().bg(bg).fg(fg))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Length(4),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(c(m.green))
+            .fg(c(m.base))
+            .add_modifier(Modifier::BOLD),
+    );
+
+    frame.render_stateful_widget(table, area, &mut app.column_chooser_state);
+}
+
+fn render_pivot_popup(frame: &mut Frame, app: &mut App, m: &catppuccin::FlavorColors) {
+    let spec = &app.pivot_setup;
+    let header = |i: usize| app.headers.get(i).cloned().unwrap_or_default();
+    let values = [
+        header(spec.rows),
+        header(spec.columns),
+        header(spec.values),
+        format!("{} ({})", spec.func.name(), spec.func.symbol()),
+        if spec.totals { "on" } else { "off" }.to_string(),
+    ];
+    let rows = form_rows(&PIVOT_FIELDS, values, m);
+    render_form_popup(
+        frame,
+        " Pivot table ",
+        rows,
+        &mut app.pivot_form_state,
+        c(m.pink),
+        m,
+    );
+}
+
+fn render_join_popup(frame: &mut Frame, app: &mut App, m: &catppuccin::FlavorColors) {
+    let spec = &app.join_setup;
+    let right_name = |i: usize| {
+        app.join_right
+            .as_ref()
+            .and_then(|df| df.get_column_names().get(i).map(|s| s.to_string()))
+            .unwrap_or_default()
+    };
+    let mut values = Vec::new();
+    for &(left, right) in &spec.keys {
+        values.push(app.headers.get(left).cloned().unwrap_or_default());
+        values.push(right_name(right));
+    }
+    values.push(spec.kind.label().to_string());
+    let fields = spec.fields();
+    let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+    let rows = form_rows(&fields, values, m);
+    render_form_popup(
+        frame,
+        " Join ",
+        rows,
+        &mut app.join_form_state,
+        c(m.flamingo),
+        m,
+    );
+}
+
+/// One `Field  ‹ value ›` row per field of a setup popup.
+fn form_rows<'a>(
+    fields: &[&'a str],
+    values: impl IntoIterator<Item = String>,
+    m: &catppuccin::FlavorColors,
+) -> Vec<Row<'a>> {
+    fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| {
+            Row::new([
+                Cell::from(*field).style(Style::default().fg(c(m.lavender))),
+                Cell::from(format!("‹ {} ›", value)),
+            ])
+            .style(Style::default().fg(c(m.text)))
+        })
+        .collect()
+}
+
+fn render_form_popup(
+    frame: &mut Frame,
+    title: &str,
+    rows: Vec<Row>,
+    state: &mut TableState,
+    accent: Color,
+    m: &catppuccin::FlavorColors,
+) {
+    let area = centered_rect(40, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(title)
+        .title_style(Style::default().fg(accent).add_modifier(Modifier::BOLD))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(accent))
+        .style(Style::default().bg(c(m.base)));
+
+    let table = Table::new(rows, [Constraint::Length(11), Constraint::Min(10)])
+        .block(block)
+        .row_highlight_style(
+            Style::default()
+                .bg(accent)
+                .fg(c(m.base))
+                .add_modifier(Modifier::BOLD),
+        );
+
+    frame.render_stateful_widget(table, area, state);
+}
+
 fn render_columns_view(frame: &mut Frame, app: &mut App, m: &catppuccin::FlavorColors) {
     let full_area = frame.area();
     frame.render_widget(Clear, full_area);
@@ -575,7 +1213,7 @@ fn render_columns_view(frame: &mut Frame, app: &mut App, m: &catppuccin::FlavorC
         .header(header)
         .block(
             Block::default()
-                .title(format!(" Column Inspector — {} ", app.file_path))
+                .title(format!(" Column Inspector — {} ", app.source_label()))
                 .title_style(Style::default().fg(c(m.green)).add_modifier(Modifier::BOLD))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
@@ -626,11 +1264,11 @@ fn downsample(data: Vec<(f64, f64)>, max_points: usize) -> Vec<(f64, f64)> {
 }
 
 fn compute_histogram(app: &App, y_idx: usize) -> Vec<(f64, f64)> {
-    let col = match app.view.column(&app.headers[y_idx]) {
-        Ok(c) => c,
-        Err(_) => return vec![],
+    let col = match app.view_column(&app.headers[y_idx]) {
+        Some(c) => c,
+        None => return vec![],
     };
-    let y_f64 = match series_to_f64(col) {
+    let y_f64 = match series_to_f64(&col) {
         Some(s) => s,
         None => return vec![],
     };
@@ -910,17 +1548,17 @@ fn collect_all_x_labels(app: &App, x_idx: usize, n_points: usize) -> Vec<String>
     if n_points == 0 {
         return vec![];
     }
-    let col = match app.view.column(&app.headers[x_idx]) {
-        Ok(c) => c,
-        Err(_) => return vec![],
+    let col = match app.view_column(&app.headers[x_idx]) {
+        Some(c) => c,
+        None => return vec![],
     };
     let s = match col.as_series() {
         Some(s) => s,
         None => return vec![],
     };
-    let str_series = match s.cast(&DataType::String) {
-        Ok(s) => s,
-        Err(_) => return vec![],
+    let str_series = match to_display_strings(s) {
+        Some(s) => s,
+        None => return vec![],
     };
     let str_ca = match str_series.str() {
         Ok(ca) => ca,
@@ -1003,14 +1641,12 @@ fn render_vertical_x_labels(
 
 fn extract_plot_data(app: &App, x_idx: usize, y_idx: usize) -> (Vec<(f64, f64)>, bool) {
     let x_series = app
-        .view
-        .column(&app.headers[x_idx])
-        .ok()
+        .view_column(&app.headers[x_idx])
+        .as_ref()
         .and_then(series_to_f64);
     let y_series = app
-        .view
-        .column(&app.headers[y_idx])
-        .ok()
+        .view_column(&app.headers[y_idx])
+        .as_ref()
         .and_then(series_to_f64);
 
     match (x_series, y_series) {